@@ -0,0 +1,318 @@
+use crate::{
+    from_c_chars, CecAdapterType, CecConfiguration, CecConnectionResult, CecConnectionResultError,
+    CecPhysicalAddress, LibcecVersion,
+};
+
+use libcec_sys::{
+    cec_adapter_descriptor, libcec_clear_configuration, libcec_configuration, libcec_connection_t,
+    libcec_destroy, libcec_detect_adapters, libcec_get_device_information, libcec_get_lib_info,
+    libcec_initialise,
+};
+use log::warn;
+use std::convert::TryFrom;
+use std::ffi::CStr;
+use std::mem;
+use std::time::Duration;
+
+/// Maximum number of adapters reported by `detect_adapters`
+const MAX_ADAPTERS: u8 = 16;
+
+/// CEC adapter found by `detect_adapters`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CecAdapterDescriptor {
+    #[doc = "name of the port, pass it to `CecConnectionCfgBuilder::port` to open the adapter"]
+    pub com_name: String,
+    #[doc = "path of the device, e.g. in sysfs"]
+    pub com_path: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub firmware_version: u16,
+    #[doc = "build date of the firmware, in seconds since epoch"]
+    pub firmware_build_date: u32,
+    #[doc = "None when the adapter did not detect its physical address"]
+    pub physical_address: Option<CecPhysicalAddress>,
+    pub adapter_type: CecAdapterType,
+}
+
+impl From<&cec_adapter_descriptor> for CecAdapterDescriptor {
+    fn from(descriptor: &cec_adapter_descriptor) -> CecAdapterDescriptor {
+        CecAdapterDescriptor {
+            com_name: from_c_chars(&descriptor.strComName),
+            com_path: from_c_chars(&descriptor.strComPath),
+            vendor_id: descriptor.iVendorId,
+            product_id: descriptor.iProductId,
+            firmware_version: descriptor.iFirmwareVersion,
+            firmware_build_date: descriptor.iFirmwareBuildDate,
+            physical_address: CecPhysicalAddress::new(descriptor.iPhysicalAddress),
+            adapter_type: CecAdapterType::from_repr(descriptor.adapterType).unwrap_or_else(|| {
+                warn!(
+                    "CecAdapterDescriptor: Could not convert adapter type {}. Using Unknown",
+                    descriptor.adapterType
+                );
+                CecAdapterType::Unknown
+            }),
+        }
+    }
+}
+
+pub type FnAdapterFilter = dyn Fn(&CecAdapterDescriptor) -> bool + Send;
+
+/// Adapter to open when several are detected, see `CecConnectionCfgBuilder::adapter`
+pub enum CecAdapterSelector {
+    #[doc = "adapter at index in the order of `detect_adapters`"]
+    Index(usize),
+    #[doc = "first adapter accepted by the filter"]
+    Filter(Box<FnAdapterFilter>),
+}
+
+impl CecAdapterSelector {
+    pub(crate) fn select<'a>(
+        &self,
+        adapters: &'a [CecAdapterDescriptor],
+    ) -> Option<&'a CecAdapterDescriptor> {
+        match self {
+            CecAdapterSelector::Index(index) => adapters.get(*index),
+            CecAdapterSelector::Filter(filter) => adapters.iter().find(|adapter| filter(adapter)),
+        }
+    }
+}
+
+/// Detect the adapters with an initialised libcec `connection`
+pub(crate) fn detect_adapters_with(connection: libcec_connection_t) -> Vec<CecAdapterDescriptor> {
+    let mut adapters =
+        vec![unsafe { mem::zeroed::<cec_adapter_descriptor>() }; MAX_ADAPTERS.into()];
+    let count = unsafe {
+        libcec_detect_adapters(
+            connection,
+            adapters.as_mut_ptr(),
+            MAX_ADAPTERS,
+            std::ptr::null(),
+            true as i32,
+        )
+    };
+    adapters
+        .iter()
+        .take(usize::try_from(count).unwrap_or(0))
+        .map(CecAdapterDescriptor::from)
+        .collect()
+}
+
+fn cleared_configuration() -> libcec_configuration {
+    let mut cfg: libcec_configuration;
+    unsafe {
+        cfg = mem::zeroed::<libcec_configuration>();
+        libcec_clear_configuration(&mut cfg);
+    }
+    cfg
+}
+
+/// libcec initialised without opening an adapter, destroyed on drop
+struct LibcecHandle(libcec_connection_t);
+
+impl LibcecHandle {
+    fn initialise() -> CecConnectionResult<LibcecHandle> {
+        let mut cfg = cleared_configuration();
+        cfg.clientVersion = LibcecVersion::Current as u32;
+        let connection = unsafe { libcec_initialise(&mut cfg) };
+        if connection as usize == 0 {
+            return Err(CecConnectionResultError::LibInitFailed);
+        }
+        Ok(LibcecHandle(connection))
+    }
+}
+
+impl Drop for LibcecHandle {
+    fn drop(&mut self) {
+        unsafe { libcec_destroy(self.0) };
+    }
+}
+
+/// Detect all CEC adapters of the system
+///
+/// # Errors
+///
+/// - LibInitFailed: libcec_sys::libcec_initialise fails
+pub fn detect_adapters() -> CecConnectionResult<Vec<CecAdapterDescriptor>> {
+    let handle = LibcecHandle::initialise()?;
+    Ok(detect_adapters_with(handle.0))
+}
+
+/// ABI of libcec
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LibcecAbi {
+    Abi4,
+    Abi5,
+    Abi6,
+    Abi7,
+}
+
+impl LibcecAbi {
+    /// ABI of the libcec this crate is compiled against
+    pub fn compiled() -> LibcecAbi {
+        if cfg!(abi7) {
+            LibcecAbi::Abi7
+        } else if cfg!(abi6) {
+            LibcecAbi::Abi6
+        } else if cfg!(abi5) {
+            LibcecAbi::Abi5
+        } else {
+            LibcecAbi::Abi4
+        }
+    }
+}
+
+/// Adapter and libcec versions, see `CecConnection::adapter_info`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CecAdapterInfo {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub adapter_type: CecAdapterType,
+    pub firmware_version: u16,
+    #[doc = "build date of the firmware, in seconds since epoch"]
+    pub firmware_build_date: u32,
+    #[doc = "version of libcec, e.g. 0x060000 for 6.0.0"]
+    pub libcec_version: u32,
+    #[doc = "libcec build information, as reported by libcec"]
+    pub lib_info: String,
+    #[doc = "ABI this crate is compiled against"]
+    pub abi: LibcecAbi,
+}
+
+impl CecAdapterInfo {
+    pub(crate) fn new(
+        vendor_id: u16,
+        product_id: u16,
+        config: &CecConfiguration,
+        lib_info: String,
+    ) -> CecAdapterInfo {
+        CecAdapterInfo {
+            vendor_id,
+            product_id,
+            adapter_type: config.adapter_type,
+            firmware_version: config.firmware_version,
+            firmware_build_date: config.firmware_build_date,
+            libcec_version: config.server_version,
+            lib_info,
+            abi: LibcecAbi::compiled(),
+        }
+    }
+
+    /// Query the adapter at `port` without opening a connection
+    ///
+    /// # Errors
+    ///
+    /// - LibInitFailed: libcec_sys::libcec_initialise fails
+    /// - AdapterOpenFailed: libcec_sys::libcec_get_device_information fails
+    pub fn for_port(port: &CStr, timeout: Duration) -> CecConnectionResult<CecAdapterInfo> {
+        let handle = LibcecHandle::initialise()?;
+        // The ids are only known from detection until the adapter is opened
+        let descriptor = detect_adapters_with(handle.0)
+            .into_iter()
+            .find(|adapter| adapter.com_name.as_bytes() == port.to_bytes());
+        let mut cfg = cleared_configuration();
+        let timeout = timeout.as_millis() as u32;
+        if unsafe { libcec_get_device_information(handle.0, port.as_ptr(), &mut cfg, timeout) } == 0
+        {
+            return Err(CecConnectionResultError::AdapterOpenFailed {
+                port: port.to_string_lossy().into_owned(),
+            });
+        }
+        Ok(CecAdapterInfo::new(
+            descriptor.as_ref().map_or(0, |adapter| adapter.vendor_id),
+            descriptor.as_ref().map_or(0, |adapter| adapter.product_id),
+            &CecConfiguration::from(&cfg),
+            lib_info(handle.0),
+        ))
+    }
+}
+
+/// Build information of libcec
+pub(crate) fn lib_info(connection: libcec_connection_t) -> String {
+    let info = unsafe { libcec_get_lib_info(connection) };
+    if info.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(info) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[cfg(test)]
+mod adapter_tests {
+    use super::*;
+
+    fn adapter(com_name: &str, adapter_type: CecAdapterType) -> CecAdapterDescriptor {
+        CecAdapterDescriptor {
+            com_name: com_name.into(),
+            com_path: String::new(),
+            vendor_id: 0,
+            product_id: 0,
+            firmware_version: 0,
+            firmware_build_date: 0,
+            physical_address: None,
+            adapter_type,
+        }
+    }
+
+    #[test]
+    fn test_from_ffi() {
+        let mut descriptor = unsafe { mem::zeroed::<cec_adapter_descriptor>() };
+        for (dst, src) in descriptor.strComName.iter_mut().zip(b"/dev/ttyACM0") {
+            *dst = *src as _;
+        }
+        for (dst, src) in descriptor.strComPath.iter_mut().zip(b"/sys/usb1") {
+            *dst = *src as _;
+        }
+        descriptor.iVendorId = 0x2548;
+        descriptor.iProductId = 0x1002;
+        descriptor.iFirmwareVersion = 12;
+        descriptor.iPhysicalAddress = 0x1000;
+        descriptor.adapterType = CecAdapterType::P8External.repr();
+        assert_eq!(
+            CecAdapterDescriptor::from(&descriptor),
+            CecAdapterDescriptor {
+                com_name: "/dev/ttyACM0".into(),
+                com_path: "/sys/usb1".into(),
+                vendor_id: 0x2548,
+                product_id: 0x1002,
+                firmware_version: 12,
+                firmware_build_date: 0,
+                physical_address: CecPhysicalAddress::from_ports([1, 0, 0, 0]),
+                adapter_type: CecAdapterType::P8External,
+            }
+        );
+
+        descriptor.iPhysicalAddress = 0xFFFF;
+        descriptor.adapterType = 0x7F;
+        let converted = CecAdapterDescriptor::from(&descriptor);
+        assert_eq!(converted.physical_address, None);
+        assert_eq!(converted.adapter_type, CecAdapterType::Unknown);
+    }
+
+    #[test]
+    fn test_abi_compiled() {
+        #[cfg(abi4)]
+        assert_eq!(LibcecAbi::compiled(), LibcecAbi::Abi4);
+        #[cfg(abi7)]
+        assert_eq!(LibcecAbi::compiled(), LibcecAbi::Abi7);
+    }
+
+    #[test]
+    fn test_select() {
+        let adapters = vec![
+            adapter("RPI", CecAdapterType::Rpi),
+            adapter("/dev/ttyACM0", CecAdapterType::P8External),
+        ];
+        assert_eq!(
+            CecAdapterSelector::Index(1).select(&adapters),
+            Some(&adapters[1])
+        );
+        assert_eq!(CecAdapterSelector::Index(2).select(&adapters), None);
+        let p8 = CecAdapterSelector::Filter(Box::new(|adapter| {
+            adapter.adapter_type == CecAdapterType::P8External
+        }));
+        assert_eq!(p8.select(&adapters), Some(&adapters[1]));
+        assert_eq!(p8.select(&adapters[..1]), None);
+    }
+}
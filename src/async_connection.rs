@@ -0,0 +1,420 @@
+use crate::{
+    CecAdapterInfo, CecCommand, CecConfiguration, CecConfigurationUpdate, CecConnection,
+    CecConnectionCfg, CecConnectionResult, CecDeviceInfo, CecDeviceType, CecEvent,
+    CecLogicalAddress, CecLogicalAddresses, CecMenuLanguage, CecMockBus, CecOpcode,
+    CecPhysicalAddress, CecPowerStatus, CecSwitchError, CecSwitchTarget, CecUserControlCode,
+    CecVendorId, CecVersion, FnEvent, KnownCecAudioStatus, TryFromCecAudioStatusError,
+    TryFromCecLogicalAddressesError,
+};
+
+use futures_core::Stream;
+use log::trace;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Stream of events reported by libcec
+///
+/// Events are queued without limit until read. Dropping the stream discards further events.
+#[derive(Debug)]
+pub struct CecEventStream(mpsc::UnboundedReceiver<CecEvent>);
+
+impl CecEventStream {
+    /// Receive next event. Returns `None` when the connection has been dropped
+    pub async fn recv(&mut self) -> Option<CecEvent> {
+        self.0.recv().await
+    }
+}
+
+impl Stream for CecEventStream {
+    type Item = CecEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<CecEvent>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+fn event_stream() -> (Box<FnEvent>, CecEventStream) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let event_callback = Box::new(move |event| {
+        if sender.send(event).is_err() {
+            trace!("CecEventStream dropped, discarding event");
+        }
+    });
+    (event_callback, CecEventStream(receiver))
+}
+
+/// Run blocking `f` on the tokio blocking thread pool, resuming panics of `f`
+async fn spawn_blocking<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(value) => value,
+        Err(err) => match err.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(err) => panic!("libcec call did not complete: {}", err),
+        },
+    }
+}
+
+/// Connection for async applications
+///
+/// All libcec calls are blocking, and run on the tokio blocking thread pool so that
+/// the executor is not blocked. Calls are serialized. Requires a tokio runtime.
+#[derive(Clone)]
+pub struct AsyncCecConnection(Arc<Mutex<CecConnection>>);
+
+impl CecConnectionCfg {
+    /// Open connection for async use, see `CecConnectionCfg::open`
+    ///
+    /// Returns the connection and a stream of all `CecEvent`s.
+    /// Callbacks set in this configuration are called as well.
+    pub async fn open_async(self) -> CecConnectionResult<(AsyncCecConnection, CecEventStream)> {
+        open_async_with(move |event_callback| self.open_with_event_callback(event_callback)).await
+    }
+}
+
+impl CecMockBus {
+    /// Open connection on the bus for async use, see `CecConnectionCfg::open_async`
+    pub async fn open_async(
+        &self,
+        config: CecConnectionCfg,
+    ) -> CecConnectionResult<(AsyncCecConnection, CecEventStream)> {
+        let bus = self.clone();
+        open_async_with(move |event_callback| bus.open_with_event_callback(config, event_callback))
+            .await
+    }
+}
+
+/// Open connection with blocking `open`, passing it the event callback of the stream
+async fn open_async_with<F>(open: F) -> CecConnectionResult<(AsyncCecConnection, CecEventStream)>
+where
+    F: FnOnce(Option<Box<FnEvent>>) -> CecConnectionResult<CecConnection> + Send + 'static,
+{
+    let (event_callback, events) = event_stream();
+    let connection = spawn_blocking(move || open(Some(event_callback))).await?;
+    Ok((AsyncCecConnection(Arc::new(Mutex::new(connection))), events))
+}
+
+impl AsyncCecConnection {
+    /// Run `f` with the blocking connection, off the executor
+    ///
+    /// Use this for the methods of `CecConnection` that do not have async counterparts.
+    pub async fn call<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce(&CecConnection) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let connection = Arc::clone(&self.0);
+        spawn_blocking(move || {
+            let connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&connection)
+        })
+        .await
+    }
+
+    pub async fn transmit(&self, command: CecCommand) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.transmit(command))
+            .await
+    }
+
+    pub async fn transmit_and_wait(
+        &self,
+        command: CecCommand,
+        expected_opcode: CecOpcode,
+        timeout: Duration,
+    ) -> CecConnectionResult<CecCommand> {
+        self.call(move |connection| connection.transmit_and_wait(command, expected_opcode, timeout))
+            .await
+    }
+
+    pub async fn send_power_on_devices(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.send_power_on_devices(address))
+            .await
+    }
+
+    pub async fn send_standby_devices(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.send_standby_devices(address))
+            .await
+    }
+
+    pub async fn set_active_source(&self, device_type: CecDeviceType) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.set_active_source(device_type))
+            .await
+    }
+
+    pub async fn get_active_source(&self) -> CecLogicalAddress {
+        self.call(|connection| connection.get_active_source()).await
+    }
+
+    pub async fn is_active_source(&self, address: CecLogicalAddress) -> bool {
+        self.call(move |connection| connection.is_active_source(address))
+            .await
+    }
+
+    pub async fn get_device_power_status(&self, address: CecLogicalAddress) -> CecPowerStatus {
+        self.call(move |connection| connection.get_device_power_status(address))
+            .await
+    }
+
+    pub async fn send_keypress(
+        &self,
+        address: CecLogicalAddress,
+        key: CecUserControlCode,
+        wait: bool,
+    ) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.send_keypress(address, key, wait))
+            .await
+    }
+
+    pub async fn send_key_release(
+        &self,
+        address: CecLogicalAddress,
+        wait: bool,
+    ) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.send_key_release(address, wait))
+            .await
+    }
+
+    pub async fn volume_up(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        self.call(move |connection| connection.volume_up(send_release))
+            .await
+    }
+
+    pub async fn volume_down(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        self.call(move |connection| connection.volume_down(send_release))
+            .await
+    }
+
+    pub async fn audio_get_status(
+        &self,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        self.call(|connection| connection.audio_get_status()).await
+    }
+
+    pub async fn set_inactive_view(&self) -> CecConnectionResult<()> {
+        self.call(|connection| connection.set_inactive_view()).await
+    }
+
+    pub async fn get_logical_addresses(
+        &self,
+    ) -> Result<CecLogicalAddresses, TryFromCecLogicalAddressesError> {
+        self.call(|connection| connection.get_logical_addresses())
+            .await
+    }
+
+    pub async fn get_device_cec_version(&self, address: CecLogicalAddress) -> CecVersion {
+        self.call(move |connection| connection.get_device_cec_version(address))
+            .await
+    }
+
+    pub async fn get_device_menu_language(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<CecMenuLanguage> {
+        self.call(move |connection| connection.get_device_menu_language(address))
+            .await
+    }
+
+    pub async fn get_device_vendor_id(&self, address: CecLogicalAddress) -> CecVendorId {
+        self.call(move |connection| connection.get_device_vendor_id(address))
+            .await
+    }
+
+    pub async fn get_device_physical_address(
+        &self,
+        address: CecLogicalAddress,
+    ) -> Option<CecPhysicalAddress> {
+        self.call(move |connection| connection.get_device_physical_address(address))
+            .await
+    }
+
+    pub async fn get_device_osd_name(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<String> {
+        self.call(move |connection| connection.get_device_osd_name(address))
+            .await
+    }
+
+    pub async fn poll_device(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.poll_device(address))
+            .await
+    }
+
+    pub async fn scan_bus(&self) -> Vec<CecDeviceInfo> {
+        self.call(|connection| connection.scan_bus()).await
+    }
+
+    pub async fn set_stream_path_logical(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.set_stream_path_logical(address))
+            .await
+    }
+
+    pub async fn set_stream_path_physical(
+        &self,
+        address: CecPhysicalAddress,
+    ) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.set_stream_path_physical(address))
+            .await
+    }
+
+    pub async fn current_configuration(&self) -> CecConnectionResult<CecConfiguration> {
+        self.call(|connection| connection.current_configuration())
+            .await
+    }
+
+    pub async fn update_configuration(
+        &self,
+        update: CecConfigurationUpdate,
+    ) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.update_configuration(&update))
+            .await
+    }
+
+    pub async fn can_persist_configuration(&self) -> bool {
+        self.call(|connection| connection.can_persist_configuration())
+            .await
+    }
+
+    pub async fn persist_configuration(
+        &self,
+        update: CecConfigurationUpdate,
+    ) -> CecConnectionResult<()> {
+        self.call(move |connection| connection.persist_configuration(&update))
+            .await
+    }
+
+    pub async fn adapter_info(&self) -> CecConnectionResult<CecAdapterInfo> {
+        self.call(|connection| connection.adapter_info()).await
+    }
+
+    pub async fn switch_to<T: Into<CecSwitchTarget>>(
+        &self,
+        target: T,
+        timeout: Duration,
+    ) -> Result<(), CecSwitchError> {
+        let target = target.into();
+        self.call(move |connection| connection.switch_to(target, timeout))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod async_connection_tests {
+    use super::*;
+    use crate::{
+        CecConnectionCfgBuilder, CecConnectionResultError, CecDeviceTypeVec, CecKeypress,
+        CecSimDevice,
+    };
+    use std::time::Duration;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let (mut event_callback, mut events) = event_stream();
+        let keypress = CecKeypress {
+            keycode: CecUserControlCode::Select,
+            duration: Duration::from_millis(100),
+        };
+        event_callback(CecEvent::Keypress(keypress));
+        runtime().block_on(async {
+            assert_eq!(events.recv().await, Some(CecEvent::Keypress(keypress)));
+        });
+        drop(event_callback);
+        runtime().block_on(async {
+            assert_eq!(events.recv().await, None);
+        });
+    }
+
+    #[test]
+    fn test_event_stream_dropped() {
+        let (mut event_callback, events) = event_stream();
+        drop(events);
+        // Must not panic
+        event_callback(CecEvent::Keypress(CecKeypress {
+            keycode: CecUserControlCode::Select,
+            duration: Duration::from_millis(100),
+        }));
+    }
+
+    #[test]
+    fn test_mock_connection() {
+        let bus = CecMockBus::new();
+        bus.add_device(CecSimDevice::tv());
+        let config = CecConnectionCfgBuilder::default()
+            .device_name("test".into())
+            .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+            .build()
+            .unwrap();
+        runtime().block_on(async {
+            let (connection, mut events) = bus.open_async(config).await.unwrap();
+            assert_eq!(
+                connection
+                    .get_device_power_status(CecLogicalAddress::Tv)
+                    .await,
+                CecPowerStatus::On
+            );
+            let reply = connection
+                .transmit_and_wait(
+                    CecCommand::give_osd_name(CecLogicalAddress::Tv).unwrap(),
+                    CecOpcode::SetOsdName,
+                    Duration::from_secs(1),
+                )
+                .await
+                .unwrap();
+            assert_eq!(reply.initiator, CecLogicalAddress::Tv);
+            assert_eq!(
+                connection.poll_device(CecLogicalAddress::Audiosystem).await,
+                Err(CecConnectionResultError::NotAcknowledged {
+                    destination: CecLogicalAddress::Audiosystem
+                })
+            );
+
+            let keypress = CecKeypress {
+                keycode: CecUserControlCode::Select,
+                duration: Duration::from_millis(100),
+            };
+            bus.inject_event(CecEvent::Keypress(keypress));
+            loop {
+                match events.recv().await {
+                    Some(CecEvent::Keypress(received)) => {
+                        assert_eq!(received, keypress);
+                        break;
+                    }
+                    Some(_) => {}
+                    None => panic!("event stream ended"),
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_spawn_blocking() {
+        assert_eq!(runtime().block_on(spawn_blocking(|| 42)), 42);
+    }
+}
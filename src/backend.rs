@@ -0,0 +1,558 @@
+use crate::adapter::{detect_adapters_with, lib_info};
+use crate::{
+    callback_table, from_c_chars, CecAdapterInfo, CecCallbacks, CecCommand, CecConfiguration,
+    CecConfigurationUpdate, CecConnectionCfg, CecConnectionResult, CecConnectionResultError,
+    CecDeckControlMode, CecDeckInfo, CecDeviceType, CecLogicalAddress, CecLogicalAddresses,
+    CecMenuLanguage, CecPhysicalAddress, CecPowerStatus, CecUserControlCode, CecVendorId,
+    CecVersion, KnownCecAudioStatus, TryFromCecAudioStatusError, TryFromCecLogicalAddressesError,
+};
+
+use libcec_sys::{
+    cec_logical_address, cec_osd_name, cec_power_status, libcec_audio_get_status,
+    libcec_audio_mute, libcec_audio_toggle_mute, libcec_audio_unmute, libcec_clear_configuration,
+    libcec_configuration, libcec_connection_t, libcec_destroy, libcec_get_active_devices,
+    libcec_get_active_source, libcec_get_adapter_product_id, libcec_get_adapter_vendor_id,
+    libcec_get_current_configuration, libcec_get_device_cec_version,
+    libcec_get_device_menu_language, libcec_get_device_osd_name,
+    libcec_get_device_physical_address, libcec_get_device_power_status,
+    libcec_get_device_vendor_id, libcec_get_logical_addresses, libcec_initialise,
+    libcec_is_active_source, libcec_mute_audio, libcec_open, libcec_poll_device,
+    libcec_power_on_devices, libcec_rescan_devices, libcec_send_key_release, libcec_send_keypress,
+    libcec_set_active_source, libcec_set_configuration, libcec_set_deck_control_mode,
+    libcec_set_deck_info, libcec_set_inactive_view, libcec_set_logical_address,
+    libcec_set_stream_path_logical, libcec_set_stream_path_physical, libcec_standby_devices,
+    libcec_switch_monitoring, libcec_transmit, libcec_volume_down, libcec_volume_up, ICECCallbacks,
+};
+use log::warn;
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::mem::{self, MaybeUninit};
+use std::os::raw::c_int;
+use std::pin::Pin;
+
+/// Operations of an open connection, implemented by libcec and by `CecMockBus`
+///
+/// `CecConnection` forwards its methods to the backend.
+pub(crate) trait CecBackend: Send + Sync {
+    fn transmit(&self, command: CecCommand) -> CecConnectionResult<()>;
+    fn send_power_on_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()>;
+    fn send_standby_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()>;
+    fn set_active_source(&self, device_type: CecDeviceType) -> CecConnectionResult<()>;
+    fn get_active_source(&self) -> CecLogicalAddress;
+    fn is_active_source(&self, address: CecLogicalAddress) -> bool;
+    fn get_device_power_status(&self, address: CecLogicalAddress) -> CecPowerStatus;
+    fn send_keypress(
+        &self,
+        address: CecLogicalAddress,
+        key: CecUserControlCode,
+        wait: bool,
+    ) -> CecConnectionResult<()>;
+    fn send_key_release(&self, address: CecLogicalAddress, wait: bool) -> CecConnectionResult<()>;
+    fn volume_up(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError>;
+    fn volume_down(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError>;
+    fn mute_audio(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError>;
+    fn audio_toggle_mute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError>;
+    fn audio_mute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError>;
+    fn audio_unmute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError>;
+    fn audio_get_status(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError>;
+    fn set_inactive_view(&self) -> CecConnectionResult<()>;
+    fn set_logical_address(&self, address: CecLogicalAddress) -> CecConnectionResult<()>;
+    fn get_logical_addresses(&self)
+        -> Result<CecLogicalAddresses, TryFromCecLogicalAddressesError>;
+    fn switch_monitoring(&self, enable: bool) -> CecConnectionResult<()>;
+    fn set_deck_control_mode(
+        &self,
+        mode: CecDeckControlMode,
+        send_update: bool,
+    ) -> CecConnectionResult<()>;
+    fn set_deck_info(&self, info: CecDeckInfo, send_update: bool) -> CecConnectionResult<()>;
+    fn get_device_cec_version(&self, address: CecLogicalAddress) -> CecVersion;
+    fn get_device_menu_language(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<CecMenuLanguage>;
+    fn get_device_vendor_id(&self, address: CecLogicalAddress) -> CecVendorId;
+    fn get_device_physical_address(&self, address: CecLogicalAddress)
+        -> Option<CecPhysicalAddress>;
+    fn get_device_osd_name(&self, address: CecLogicalAddress) -> CecConnectionResult<String>;
+    fn poll_device(&self, address: CecLogicalAddress) -> CecConnectionResult<()>;
+    fn rescan_devices(&self);
+    fn get_active_devices(&self) -> Vec<CecLogicalAddress>;
+    fn set_stream_path_logical(&self, address: CecLogicalAddress) -> CecConnectionResult<()>;
+    fn set_stream_path_physical(&self, address: CecPhysicalAddress) -> CecConnectionResult<()>;
+    fn current_configuration(&self) -> CecConnectionResult<CecConfiguration>;
+    fn update_configuration(&self, update: &CecConfigurationUpdate) -> CecConnectionResult<()>;
+    fn can_persist_configuration(&self) -> bool;
+    fn persist_configuration(&self, update: &CecConfigurationUpdate) -> CecConnectionResult<()>;
+    fn adapter_info(&self) -> CecConnectionResult<CecAdapterInfo>;
+}
+
+/// Error for libcec failing to send a command to `destination`
+///
+/// libcec does not tell why sending failed, for directed commands the usual reason is
+/// that the destination did not acknowledge the command.
+fn send_error(destination: CecLogicalAddress) -> CecConnectionResultError {
+    match destination {
+        CecLogicalAddress::Unregistered | CecLogicalAddress::Unknown => {
+            CecConnectionResultError::TransmitFailed
+        }
+        _ => CecConnectionResultError::NotAcknowledged { destination },
+    }
+}
+
+/// Result of a libcec query like `libcec_get_device_osd_name`, which returns 0 on
+/// success and -1 when the connection is not open
+fn query_result(ret: c_int, address: CecLogicalAddress) -> CecConnectionResult<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(CecConnectionResultError::Timeout {
+            destination: address,
+        })
+    }
+}
+
+/// Connection to a CEC adapter through libcec, owning the callbacks registered with it
+pub(crate) struct LibcecBackend(
+    libcec_connection_t,
+    #[allow(dead_code)] Pin<Box<CecCallbacks>>,
+    #[allow(dead_code)] Pin<Box<ICECCallbacks>>,
+);
+
+impl LibcecBackend {
+    /// Initialise libcec, open the adapter and register `callbacks`
+    pub(crate) fn open(
+        config: &CecConnectionCfg,
+        callbacks: CecCallbacks,
+    ) -> CecConnectionResult<LibcecBackend> {
+        let mut cfg: libcec_configuration = config.into();
+        let pinned_callbacks = Box::pin(callbacks);
+        let rust_callbacks_as_void_ptr = &*pinned_callbacks as *const _ as *mut _;
+        let mut pinned_table = Box::pin(callback_table());
+        let table_ptr: *mut ICECCallbacks = &mut *pinned_table;
+        let backend = LibcecBackend(
+            unsafe { libcec_initialise(&mut cfg) },
+            pinned_callbacks,
+            pinned_table,
+        );
+        if backend.0 as usize == 0 {
+            return Err(CecConnectionResultError::LibInitFailed);
+        }
+
+        let open_timeout = config.open_timeout.as_millis() as u32;
+        match &config.port {
+            Some(port) => {
+                if unsafe { libcec_open(backend.0, port.as_ptr(), open_timeout) } == 0 {
+                    return Err(CecConnectionResultError::AdapterOpenFailed {
+                        port: port.to_string_lossy().into_owned(),
+                    });
+                }
+            }
+            None => {
+                let adapters = detect_adapters_with(backend.0);
+                let adapter = match &config.adapter {
+                    Some(selector) => selector.select(&adapters),
+                    None => adapters.first(),
+                }
+                .ok_or(CecConnectionResultError::NoAdapterFound)?;
+                let port = CString::new(adapter.com_name.as_str()).map_err(|_| {
+                    CecConnectionResultError::InvalidArgument(format!(
+                        "adapter port {:?} contains NUL",
+                        adapter.com_name
+                    ))
+                })?;
+                if unsafe { libcec_open(backend.0, port.as_ptr(), open_timeout) == 0 } {
+                    return Err(CecConnectionResultError::AdapterOpenFailed {
+                        port: adapter.com_name.clone(),
+                    });
+                }
+            }
+        };
+
+        #[cfg(abi4)]
+        let callback_ret = unsafe {
+            libcec_sys::libcec_enable_callbacks(backend.0, rust_callbacks_as_void_ptr, table_ptr)
+        };
+        #[cfg(not(abi4))]
+        let callback_ret = unsafe {
+            libcec_sys::libcec_set_callbacks(backend.0, table_ptr, rust_callbacks_as_void_ptr)
+        };
+        if callback_ret == 0 {
+            return Err(CecConnectionResultError::CallbackRegistrationFailed);
+        }
+
+        Ok(backend)
+    }
+
+    /// Configuration currently used by libcec
+    fn libcec_configuration(&self) -> CecConnectionResult<libcec_configuration> {
+        let mut cfg = MaybeUninit::<libcec_configuration>::zeroed();
+        unsafe { libcec_clear_configuration(cfg.as_mut_ptr()) };
+        if unsafe { libcec_get_current_configuration(self.0, cfg.as_mut_ptr()) } == 0 {
+            return Err(CecConnectionResultError::ConfigurationFailed);
+        }
+        Ok(unsafe { cfg.assume_init() })
+    }
+}
+
+impl CecBackend for LibcecBackend {
+    fn transmit(&self, command: CecCommand) -> CecConnectionResult<()> {
+        let destination = command.destination;
+        if unsafe { libcec_transmit(self.0, &command.into()) } == 0 {
+            Err(send_error(destination))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn send_power_on_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        if unsafe { libcec_power_on_devices(self.0, address.repr()) } == 0 {
+            Err(send_error(address))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn send_standby_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        if unsafe { libcec_standby_devices(self.0, address.repr()) } == 0 {
+            Err(send_error(address))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_active_source(&self, device_type: CecDeviceType) -> CecConnectionResult<()> {
+        if unsafe { libcec_set_active_source(self.0, device_type.repr()) } == 0 {
+            Err(CecConnectionResultError::TransmitFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_active_source(&self) -> CecLogicalAddress {
+        let active_raw: cec_logical_address = unsafe { libcec_get_active_source(self.0) };
+        match CecLogicalAddress::from_repr(active_raw) {
+            Some(address) => address,
+            None => {
+                warn!("get_active_source: Could not convert logical address {} to rust enum. Returning Unknown", active_raw);
+                CecLogicalAddress::Unknown
+            }
+        }
+    }
+
+    fn is_active_source(&self, address: CecLogicalAddress) -> bool {
+        (unsafe { libcec_is_active_source(self.0, address.repr()) }) != 0
+    }
+
+    fn get_device_power_status(&self, address: CecLogicalAddress) -> CecPowerStatus {
+        let status_raw: cec_power_status =
+            unsafe { libcec_get_device_power_status(self.0, address.repr()) };
+        match CecPowerStatus::from_repr(status_raw) {
+            Some(status) => status,
+            None => {
+                warn!("get_device_power_status: Could not convert result {} to rust enum. Returning Unknown", status_raw);
+                CecPowerStatus::Unknown
+            }
+        }
+    }
+
+    fn send_keypress(
+        &self,
+        address: CecLogicalAddress,
+        key: CecUserControlCode,
+        wait: bool,
+    ) -> CecConnectionResult<()> {
+        if unsafe { libcec_send_keypress(self.0, address.repr(), key.repr(), wait.into()) } == 0 {
+            Err(send_error(address))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn send_key_release(&self, address: CecLogicalAddress, wait: bool) -> CecConnectionResult<()> {
+        if unsafe { libcec_send_key_release(self.0, address.repr(), wait.into()) } == 0 {
+            Err(send_error(address))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn volume_up(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        match u8::try_from(unsafe { libcec_volume_up(self.0, send_release.into()) }) {
+            Ok(status) => KnownCecAudioStatus::try_from(status),
+            Err(_) => Err(TryFromCecAudioStatusError::Unknown),
+        }
+    }
+
+    fn volume_down(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        match u8::try_from(unsafe { libcec_volume_down(self.0, send_release.into()) }) {
+            Ok(status) => KnownCecAudioStatus::try_from(status),
+            Err(_) => Err(TryFromCecAudioStatusError::Unknown),
+        }
+    }
+
+    fn mute_audio(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        match u8::try_from(unsafe { libcec_mute_audio(self.0, send_release.into()) }) {
+            Ok(status) => KnownCecAudioStatus::try_from(status),
+            Err(_) => Err(TryFromCecAudioStatusError::Unknown),
+        }
+    }
+
+    fn audio_toggle_mute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        KnownCecAudioStatus::try_from(unsafe { libcec_audio_toggle_mute(self.0) })
+    }
+
+    fn audio_mute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        KnownCecAudioStatus::try_from(unsafe { libcec_audio_mute(self.0) })
+    }
+
+    fn audio_unmute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        KnownCecAudioStatus::try_from(unsafe { libcec_audio_unmute(self.0) })
+    }
+
+    fn audio_get_status(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        KnownCecAudioStatus::try_from(unsafe { libcec_audio_get_status(self.0) })
+    }
+
+    fn set_inactive_view(&self) -> CecConnectionResult<()> {
+        if unsafe { libcec_set_inactive_view(self.0) } == 0 {
+            Err(CecConnectionResultError::TransmitFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_logical_address(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        if unsafe { libcec_set_logical_address(self.0, address.repr()) } == 0 {
+            Err(CecConnectionResultError::TransmitFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_logical_addresses(
+        &self,
+    ) -> Result<CecLogicalAddresses, TryFromCecLogicalAddressesError> {
+        CecLogicalAddresses::try_from(unsafe { libcec_get_logical_addresses(self.0) })
+    }
+
+    fn switch_monitoring(&self, enable: bool) -> CecConnectionResult<()> {
+        if unsafe { libcec_switch_monitoring(self.0, enable.into()) } == 0 {
+            Err(CecConnectionResultError::TransmitFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_deck_control_mode(
+        &self,
+        mode: CecDeckControlMode,
+        send_update: bool,
+    ) -> CecConnectionResult<()> {
+        if unsafe { libcec_set_deck_control_mode(self.0, mode.repr(), send_update.into()) } == 0 {
+            Err(CecConnectionResultError::TransmitFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_deck_info(&self, info: CecDeckInfo, send_update: bool) -> CecConnectionResult<()> {
+        if unsafe { libcec_set_deck_info(self.0, info.repr(), send_update.into()) } == 0 {
+            Err(CecConnectionResultError::TransmitFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_device_cec_version(&self, address: CecLogicalAddress) -> CecVersion {
+        let version_raw = unsafe { libcec_get_device_cec_version(self.0, address.repr()) };
+        match CecVersion::from_repr(version_raw) {
+            Some(version) => version,
+            None => {
+                warn!("get_device_cec_version: Could not convert result {} to rust enum. Returning VersionUnknown", version_raw);
+                CecVersion::VersionUnknown
+            }
+        }
+    }
+
+    fn get_device_menu_language(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<CecMenuLanguage> {
+        let mut language: [::std::os::raw::c_char; 4] = [0; 4];
+        query_result(
+            unsafe {
+                libcec_get_device_menu_language(self.0, address.repr(), language.as_mut_ptr())
+            },
+            address,
+        )?;
+        // libcec reports "???" when the device did not reply
+        CecMenuLanguage::new(&from_c_chars(&language)).ok_or(CecConnectionResultError::Timeout {
+            destination: address,
+        })
+    }
+
+    fn get_device_vendor_id(&self, address: CecLogicalAddress) -> CecVendorId {
+        CecVendorId::from(unsafe { libcec_get_device_vendor_id(self.0, address.repr()) })
+    }
+
+    fn get_device_physical_address(
+        &self,
+        address: CecLogicalAddress,
+    ) -> Option<CecPhysicalAddress> {
+        CecPhysicalAddress::new(unsafe {
+            libcec_get_device_physical_address(self.0, address.repr())
+        })
+    }
+
+    fn get_device_osd_name(&self, address: CecLogicalAddress) -> CecConnectionResult<String> {
+        // libcec fills up to the whole cec_osd_name without a NUL terminator, from_c_chars
+        // stops at the end of the buffer
+        let mut name: cec_osd_name = [0; mem::size_of::<cec_osd_name>()];
+        query_result(
+            unsafe { libcec_get_device_osd_name(self.0, address.repr(), name.as_mut_ptr()) },
+            address,
+        )?;
+        Ok(from_c_chars(&name))
+    }
+
+    fn poll_device(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        if unsafe { libcec_poll_device(self.0, address.repr()) } == 0 {
+            Err(send_error(address))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn rescan_devices(&self) {
+        unsafe { libcec_rescan_devices(self.0) }
+    }
+
+    fn get_active_devices(&self) -> Vec<CecLogicalAddress> {
+        let devices = unsafe { libcec_get_active_devices(self.0) };
+        // If logical address x is in use, devices.addresses[x] != 0.
+        (0u8..)
+            .zip(devices.addresses.iter())
+            .filter(|(_, addr_mask)| **addr_mask != 0)
+            .filter_map(|(address, _)| CecLogicalAddress::from_repr(address.into()))
+            .collect()
+    }
+
+    fn set_stream_path_logical(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        if unsafe { libcec_set_stream_path_logical(self.0, address.repr()) } == 0 {
+            Err(CecConnectionResultError::TransmitFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_stream_path_physical(&self, address: CecPhysicalAddress) -> CecConnectionResult<()> {
+        if unsafe { libcec_set_stream_path_physical(self.0, address.into()) } == 0 {
+            Err(CecConnectionResultError::TransmitFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn current_configuration(&self) -> CecConnectionResult<CecConfiguration> {
+        Ok(CecConfiguration::from(&self.libcec_configuration()?))
+    }
+
+    fn update_configuration(&self, update: &CecConfigurationUpdate) -> CecConnectionResult<()> {
+        let mut cfg = self.libcec_configuration()?;
+        update.apply_to_libcec(&mut cfg);
+        if unsafe { libcec_set_configuration(self.0, &cfg) } == 0 {
+            Err(CecConnectionResultError::ConfigurationFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn can_persist_configuration(&self) -> bool {
+        #[cfg(any(abi4, abi5))]
+        let can_persist = unsafe { libcec_sys::libcec_can_persist_configuration(self.0) };
+        #[cfg(not(any(abi4, abi5)))]
+        let can_persist = unsafe { libcec_sys::libcec_can_save_configuration(self.0) };
+        // -1 when the connection is not open
+        can_persist == 1
+    }
+
+    fn persist_configuration(&self, update: &CecConfigurationUpdate) -> CecConnectionResult<()> {
+        if !self.can_persist_configuration() {
+            return Err(CecConnectionResultError::ConfigurationFailed);
+        }
+        let mut cfg = self.libcec_configuration()?;
+        update.apply_to_libcec(&mut cfg);
+        // Since libcec 6 setting the configuration also saves it in the adapter
+        #[cfg(any(abi4, abi5))]
+        let persisted = unsafe { libcec_sys::libcec_persist_configuration(self.0, &mut cfg) };
+        #[cfg(not(any(abi4, abi5)))]
+        let persisted = unsafe { libcec_set_configuration(self.0, &cfg) };
+        if persisted == 0 {
+            Err(CecConnectionResultError::ConfigurationFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn adapter_info(&self) -> CecConnectionResult<CecAdapterInfo> {
+        Ok(CecAdapterInfo::new(
+            unsafe { libcec_get_adapter_vendor_id(self.0) },
+            unsafe { libcec_get_adapter_product_id(self.0) },
+            &CecConfiguration::from(&self.libcec_configuration()?),
+            lib_info(self.0),
+        ))
+    }
+}
+
+impl Drop for LibcecBackend {
+    fn drop(&mut self) {
+        unsafe {
+            libcec_destroy(self.0);
+        }
+    }
+}
+
+// libcec doesn't use thread local storage, so it's safe to send
+// LibcecBackend across threads.
+unsafe impl Send for LibcecBackend {}
+
+// libcec guards interior mutability behind mutex locks. It's hard to
+// verify that their implementation is perfectly thread safe, but they
+// wouldn't be using mutexes if they weren't aiming for thread safety,
+// so we trust that their implementation is correct.
+unsafe impl Sync for LibcecBackend {}
+
+#[cfg(test)]
+mod backend_tests {
+    use super::*;
+
+    #[test]
+    fn test_query_result() {
+        assert_eq!(query_result(0, CecLogicalAddress::Tv), Ok(()));
+        assert_eq!(
+            query_result(-1, CecLogicalAddress::Tv),
+            Err(CecConnectionResultError::Timeout {
+                destination: CecLogicalAddress::Tv
+            })
+        );
+    }
+}
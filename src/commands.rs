@@ -0,0 +1,861 @@
+use crate::{
+    CecAbortReason, CecAnalogueService, CecAudioRate, CecCommand, CecDatapacket,
+    CecDeckControlMode, CecDeckInfo, CecDeviceType, CecDisplayControl, CecExternalSource,
+    CecLogicalAddress, CecMenuLanguage, CecMenuRequestType, CecMenuState, CecMessage, CecOpcode,
+    CecPhysicalAddress, CecPlayMode, CecPowerStatus, CecRecordSource, CecRecordStatusInfo,
+    CecRecordingFlag, CecServiceId, CecStatusRequest, CecSystemAudioStatus,
+    CecTimerClearedStatusData, CecTimerInfo, CecTimerStatus, CecTunerDisplayInfo,
+    CecUserControlCode, CecVendorId, CecVersion, KnownCecAudioStatus, OSD_NAME_MAX_LENGTH,
+    OSD_STRING_MAX_LENGTH, PROGRAM_TITLE_MAX_LENGTH,
+};
+
+use arrayvec::ArrayVec;
+use std::fmt;
+
+/// Addressing allowed for a message by the CEC specification
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CecMessageAddressing {
+    /// Message must be sent to a single device
+    Directed,
+    /// Message must be broadcast
+    Broadcast,
+    /// Message can be either directed or broadcast
+    Either,
+}
+
+/// Error constructing `CecCommand` from a message
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CecCommandBuildError {
+    /// Message must be directed, but destination is broadcast
+    BroadcastNotAllowed { opcode: CecOpcode },
+    /// Message must be broadcast, but destination is a single device
+    DirectedNotAllowed { opcode: CecOpcode },
+    /// Destination is `CecLogicalAddress::Unknown`
+    InvalidDestination { opcode: CecOpcode },
+    /// Parameter is out of range, e.g. too long string or empty list
+    InvalidParameter { opcode: CecOpcode },
+}
+
+impl fmt::Display for CecCommandBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CecCommandBuildError::BroadcastNotAllowed { opcode } => {
+                write!(f, "{:?} must be sent to a single device", opcode)
+            }
+            CecCommandBuildError::DirectedNotAllowed { opcode } => {
+                write!(f, "{:?} must be broadcast", opcode)
+            }
+            CecCommandBuildError::InvalidDestination { opcode } => {
+                write!(f, "{:?} sent to unknown destination", opcode)
+            }
+            CecCommandBuildError::InvalidParameter { opcode } => {
+                write!(f, "{:?} parameter out of range", opcode)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CecCommandBuildError {}
+
+/// Maximum number of parameter bytes in a CEC frame (16 blocks minus header and opcode)
+const MAX_PARAMETERS_LENGTH: usize = 14;
+
+impl CecMessage {
+    /// Addressing of the message, as defined by the CEC specification
+    pub fn addressing(&self) -> CecMessageAddressing {
+        match self {
+            CecMessage::ActiveSource { .. }
+            | CecMessage::RequestActiveSource
+            | CecMessage::RoutingChange { .. }
+            | CecMessage::RoutingInformation { .. }
+            | CecMessage::SetStreamPath { .. }
+            | CecMessage::ReportPhysicalAddress { .. }
+            | CecMessage::SetMenuLanguage { .. }
+            | CecMessage::DeviceVendorId { .. }
+            | CecMessage::Cdc { .. } => CecMessageAddressing::Broadcast,
+            CecMessage::Standby
+            | CecMessage::VendorCommandWithId { .. }
+            | CecMessage::VendorRemoteButtonDown { .. }
+            | CecMessage::VendorRemoteButtonUp
+            | CecMessage::ReportPowerStatus { .. }
+            | CecMessage::SetSystemAudioMode { .. } => CecMessageAddressing::Either,
+            _ => CecMessageAddressing::Directed,
+        }
+    }
+}
+
+fn check_string(value: &str, max_length: usize) -> bool {
+    !value.is_empty() && value.len() <= max_length && value.is_ascii()
+}
+
+fn check_length(data: &CecDatapacket, max_length: usize) -> bool {
+    !data.0.is_empty() && data.0.len() <= max_length
+}
+
+fn check_timer(timer: &CecTimerInfo) -> bool {
+    (1..=31).contains(&timer.day_of_month)
+        && (1..=12).contains(&timer.month)
+        && timer.start_hour <= 23
+        && timer.start_minute <= 59
+        && timer.duration_hours <= 99
+        && timer.duration_minutes <= 59
+}
+
+/// Check that message parameters can be encoded without truncation
+fn check_parameters(message: &CecMessage) -> bool {
+    match message {
+        CecMessage::SetOsdName { name } => check_string(name, OSD_NAME_MAX_LENGTH),
+        CecMessage::SetOsdString { text, .. } => check_string(text, OSD_STRING_MAX_LENGTH),
+        CecMessage::SetTimerProgramTitle { title } => check_string(title, PROGRAM_TITLE_MAX_LENGTH),
+        CecMessage::VendorCommand { data } => check_length(data, MAX_PARAMETERS_LENGTH),
+        CecMessage::VendorCommandWithId { data, .. } => {
+            check_length(data, MAX_PARAMETERS_LENGTH - 3)
+        }
+        CecMessage::VendorRemoteButtonDown { rc_code } => {
+            check_length(rc_code, MAX_PARAMETERS_LENGTH)
+        }
+        CecMessage::Cdc { data, .. } => check_length(data, MAX_PARAMETERS_LENGTH - 2),
+        CecMessage::ReportShortAudioDescriptors { descriptors } => !descriptors.is_empty(),
+        CecMessage::RequestShortAudioDescriptors { formats } => !formats.is_empty(),
+        CecMessage::ClearAnalogueTimer { timer, .. }
+        | CecMessage::ClearDigitalTimer { timer, .. }
+        | CecMessage::ClearExternalTimer { timer, .. }
+        | CecMessage::SetAnalogueTimer { timer, .. }
+        | CecMessage::SetDigitalTimer { timer, .. }
+        | CecMessage::SetExternalTimer { timer, .. } => check_timer(timer),
+        CecMessage::TimerStatus { status } => match status.duration_available {
+            Some((hours, minutes)) => hours <= 99 && minutes <= 59,
+            None => true,
+        },
+        _ => true,
+    }
+}
+
+fn datapacket(
+    opcode: CecOpcode,
+    data: &[u8],
+    max_length: usize,
+) -> Result<CecDatapacket, CecCommandBuildError> {
+    let mut packet = CecDatapacket(ArrayVec::new());
+    if data.is_empty() || data.len() > max_length {
+        return Err(CecCommandBuildError::InvalidParameter { opcode });
+    }
+    packet.0.try_extend_from_slice(data).unwrap();
+    Ok(packet)
+}
+
+fn array_vec<T: Copy, const N: usize>(
+    opcode: CecOpcode,
+    data: &[T],
+) -> Result<ArrayVec<T, N>, CecCommandBuildError> {
+    if data.is_empty() || data.len() > N {
+        return Err(CecCommandBuildError::InvalidParameter { opcode });
+    }
+    Ok(data.iter().copied().collect())
+}
+
+impl CecCommand {
+    /// Create command from message, checking addressing and parameters
+    ///
+    /// Initiator is left as `CecLogicalAddress::Unknown`, which `CecConnection::transmit`
+    /// replaces with the primary logical address of the connection.
+    /// Use `CecLogicalAddress::Unregistered` as `destination` to broadcast.
+    ///
+    /// # Errors
+    ///
+    /// Error is returned in following cases
+    /// - BroadcastNotAllowed: message must be directed, but destination is broadcast
+    /// - DirectedNotAllowed: message must be broadcast, but destination is a single device
+    /// - InvalidDestination: destination is `Unknown`
+    /// - InvalidParameter: message parameters cannot be encoded, e.g. too long OSD name
+    pub fn from_message(
+        destination: CecLogicalAddress,
+        message: CecMessage,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        let opcode = message.opcode();
+        match (destination, message.addressing()) {
+            (CecLogicalAddress::Unknown, _) => {
+                return Err(CecCommandBuildError::InvalidDestination { opcode })
+            }
+            (CecLogicalAddress::Unregistered, CecMessageAddressing::Directed) => {
+                return Err(CecCommandBuildError::BroadcastNotAllowed { opcode })
+            }
+            (CecLogicalAddress::Unregistered, _) => {}
+            (_, CecMessageAddressing::Broadcast) => {
+                return Err(CecCommandBuildError::DirectedNotAllowed { opcode })
+            }
+            _ => {}
+        }
+        if !check_parameters(&message) {
+            return Err(CecCommandBuildError::InvalidParameter { opcode });
+        }
+        Ok(message.to_command(CecLogicalAddress::Unknown, destination))
+    }
+
+    /// Broadcast message whose parameters are valid by construction
+    fn broadcast(message: CecMessage) -> CecCommand {
+        message.to_command(CecLogicalAddress::Unknown, CecLogicalAddress::Unregistered)
+    }
+
+    // One Touch Play, Routing Control and System Standby
+
+    pub fn active_source(address: CecPhysicalAddress) -> CecCommand {
+        Self::broadcast(CecMessage::ActiveSource { address })
+    }
+
+    pub fn image_view_on(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::ImageViewOn)
+    }
+
+    pub fn text_view_on(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::TextViewOn)
+    }
+
+    /// `<Inactive Source>` is always directed to the TV
+    pub fn inactive_source(address: CecPhysicalAddress) -> CecCommand {
+        CecMessage::InactiveSource { address }
+            .to_command(CecLogicalAddress::Unknown, CecLogicalAddress::Tv)
+    }
+
+    pub fn request_active_source() -> CecCommand {
+        Self::broadcast(CecMessage::RequestActiveSource)
+    }
+
+    pub fn routing_change(
+        original_address: CecPhysicalAddress,
+        new_address: CecPhysicalAddress,
+    ) -> CecCommand {
+        Self::broadcast(CecMessage::RoutingChange {
+            original_address,
+            new_address,
+        })
+    }
+
+    pub fn routing_information(address: CecPhysicalAddress) -> CecCommand {
+        Self::broadcast(CecMessage::RoutingInformation { address })
+    }
+
+    pub fn set_stream_path(address: CecPhysicalAddress) -> CecCommand {
+        Self::broadcast(CecMessage::SetStreamPath { address })
+    }
+
+    /// `<Standby>` to single device, or to all devices with `CecLogicalAddress::Unregistered`
+    pub fn standby(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::Standby)
+    }
+
+    // One Touch Record and Timer Programming
+
+    pub fn record_off(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::RecordOff)
+    }
+
+    pub fn record_on(
+        to: CecLogicalAddress,
+        source: CecRecordSource,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::RecordOn { source })
+    }
+
+    pub fn record_status(
+        to: CecLogicalAddress,
+        status: CecRecordStatusInfo,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::RecordStatus { status })
+    }
+
+    pub fn record_tv_screen(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::RecordTvScreen)
+    }
+
+    pub fn clear_analogue_timer(
+        to: CecLogicalAddress,
+        timer: CecTimerInfo,
+        service: CecAnalogueService,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::ClearAnalogueTimer { timer, service })
+    }
+
+    pub fn clear_digital_timer(
+        to: CecLogicalAddress,
+        timer: CecTimerInfo,
+        service: [u8; 7],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::ClearDigitalTimer { timer, service })
+    }
+
+    pub fn clear_external_timer(
+        to: CecLogicalAddress,
+        timer: CecTimerInfo,
+        source: CecExternalSource,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::ClearExternalTimer { timer, source })
+    }
+
+    pub fn set_analogue_timer(
+        to: CecLogicalAddress,
+        timer: CecTimerInfo,
+        service: CecAnalogueService,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SetAnalogueTimer { timer, service })
+    }
+
+    pub fn set_digital_timer(
+        to: CecLogicalAddress,
+        timer: CecTimerInfo,
+        service: [u8; 7],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SetDigitalTimer { timer, service })
+    }
+
+    pub fn set_external_timer(
+        to: CecLogicalAddress,
+        timer: CecTimerInfo,
+        source: CecExternalSource,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SetExternalTimer { timer, source })
+    }
+
+    pub fn set_timer_program_title(
+        to: CecLogicalAddress,
+        title: &str,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(
+            to,
+            CecMessage::SetTimerProgramTitle {
+                title: title.into(),
+            },
+        )
+    }
+
+    pub fn timer_cleared_status(
+        to: CecLogicalAddress,
+        status: CecTimerClearedStatusData,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::TimerClearedStatus { status })
+    }
+
+    pub fn timer_status(
+        to: CecLogicalAddress,
+        status: CecTimerStatus,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::TimerStatus { status })
+    }
+
+    // System Information
+
+    pub fn cec_version(
+        to: CecLogicalAddress,
+        version: CecVersion,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::CecVersion { version })
+    }
+
+    pub fn get_cec_version(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GetCecVersion)
+    }
+
+    pub fn give_physical_address(
+        to: CecLogicalAddress,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GivePhysicalAddress)
+    }
+
+    pub fn get_menu_language(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GetMenuLanguage)
+    }
+
+    pub fn report_physical_address(
+        address: CecPhysicalAddress,
+        device_type: CecDeviceType,
+    ) -> CecCommand {
+        Self::broadcast(CecMessage::ReportPhysicalAddress {
+            address,
+            device_type,
+        })
+    }
+
+    pub fn set_menu_language(language: CecMenuLanguage) -> CecCommand {
+        Self::broadcast(CecMessage::SetMenuLanguage { language })
+    }
+
+    // Deck Control
+
+    pub fn deck_control(
+        to: CecLogicalAddress,
+        mode: CecDeckControlMode,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::DeckControl { mode })
+    }
+
+    pub fn deck_status(
+        to: CecLogicalAddress,
+        info: CecDeckInfo,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::DeckStatus { info })
+    }
+
+    pub fn give_deck_status(
+        to: CecLogicalAddress,
+        request: CecStatusRequest,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GiveDeckStatus { request })
+    }
+
+    pub fn play(
+        to: CecLogicalAddress,
+        mode: CecPlayMode,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::Play { mode })
+    }
+
+    // Tuner Control
+
+    pub fn give_tuner_device_status(
+        to: CecLogicalAddress,
+        request: CecStatusRequest,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GiveTunerDeviceStatus { request })
+    }
+
+    pub fn select_analogue_service(
+        to: CecLogicalAddress,
+        service: CecAnalogueService,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SelectAnalogueService { service })
+    }
+
+    pub fn select_digital_service(
+        to: CecLogicalAddress,
+        service: [u8; 7],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SelectDigitalService { service })
+    }
+
+    pub fn tuner_device_status(
+        to: CecLogicalAddress,
+        recording_flag: CecRecordingFlag,
+        display_info: CecTunerDisplayInfo,
+        service: Option<CecServiceId>,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(
+            to,
+            CecMessage::TunerDeviceStatus {
+                recording_flag,
+                display_info,
+                service,
+            },
+        )
+    }
+
+    pub fn tuner_step_decrement(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::TunerStepDecrement)
+    }
+
+    pub fn tuner_step_increment(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::TunerStepIncrement)
+    }
+
+    // Vendor Specific Commands
+
+    pub fn device_vendor_id(vendor_id: CecVendorId) -> CecCommand {
+        Self::broadcast(CecMessage::DeviceVendorId { vendor_id })
+    }
+
+    pub fn give_device_vendor_id(
+        to: CecLogicalAddress,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GiveDeviceVendorId)
+    }
+
+    /// `<Vendor Command>` with 1 to 14 bytes of vendor specific data
+    pub fn vendor_command(
+        to: CecLogicalAddress,
+        data: &[u8],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        let data = datapacket(CecOpcode::VendorCommand, data, MAX_PARAMETERS_LENGTH)?;
+        Self::from_message(to, CecMessage::VendorCommand { data })
+    }
+
+    /// `<Vendor Command With ID>` with 1 to 11 bytes of vendor specific data
+    pub fn vendor_command_with_id(
+        to: CecLogicalAddress,
+        vendor_id: CecVendorId,
+        data: &[u8],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        let data = datapacket(
+            CecOpcode::VendorCommandWithId,
+            data,
+            MAX_PARAMETERS_LENGTH - 3,
+        )?;
+        Self::from_message(to, CecMessage::VendorCommandWithId { vendor_id, data })
+    }
+
+    /// `<Vendor Remote Button Down>` with 1 to 14 bytes of vendor specific RC code
+    pub fn vendor_remote_button_down(
+        to: CecLogicalAddress,
+        rc_code: &[u8],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        let rc_code = datapacket(
+            CecOpcode::VendorRemoteButtonDown,
+            rc_code,
+            MAX_PARAMETERS_LENGTH,
+        )?;
+        Self::from_message(to, CecMessage::VendorRemoteButtonDown { rc_code })
+    }
+
+    pub fn vendor_remote_button_up(
+        to: CecLogicalAddress,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::VendorRemoteButtonUp)
+    }
+
+    // OSD Display and Device OSD Transfer
+
+    /// `<Set OSD String>` with 1 to 13 ASCII characters
+    pub fn set_osd_string(
+        to: CecLogicalAddress,
+        display_control: CecDisplayControl,
+        text: &str,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(
+            to,
+            CecMessage::SetOsdString {
+                display_control,
+                text: text.into(),
+            },
+        )
+    }
+
+    pub fn give_osd_name(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GiveOsdName)
+    }
+
+    /// `<Set OSD Name>` with 1 to 14 ASCII characters
+    pub fn set_osd_name(
+        to: CecLogicalAddress,
+        name: &str,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SetOsdName { name: name.into() })
+    }
+
+    // Device Menu Control and Remote Control Passthrough
+
+    pub fn menu_request(
+        to: CecLogicalAddress,
+        request: CecMenuRequestType,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::MenuRequest { request })
+    }
+
+    pub fn menu_status(
+        to: CecLogicalAddress,
+        state: CecMenuState,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::MenuStatus { state })
+    }
+
+    pub fn user_control_pressed(
+        to: CecLogicalAddress,
+        keycode: CecUserControlCode,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::UserControlPressed { keycode })
+    }
+
+    pub fn user_control_release(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::UserControlRelease)
+    }
+
+    // Power Status
+
+    pub fn give_device_power_status(
+        to: CecLogicalAddress,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GiveDevicePowerStatus)
+    }
+
+    pub fn report_power_status(
+        to: CecLogicalAddress,
+        status: CecPowerStatus,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::ReportPowerStatus { status })
+    }
+
+    // General Protocol
+
+    pub fn feature_abort(
+        to: CecLogicalAddress,
+        opcode: CecOpcode,
+        reason: CecAbortReason,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::FeatureAbort { opcode, reason })
+    }
+
+    pub fn abort(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::Abort)
+    }
+
+    /// Message without opcode, used to check whether a logical address is taken
+    pub fn poll(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::Poll)
+    }
+
+    // System Audio Control and Audio Rate Control
+
+    pub fn give_audio_status(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GiveAudioStatus)
+    }
+
+    pub fn give_system_audio_mode_status(
+        to: CecLogicalAddress,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::GiveSystemAudioModeStatus)
+    }
+
+    pub fn report_audio_status(
+        to: CecLogicalAddress,
+        status: KnownCecAudioStatus,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::ReportAudioStatus { status })
+    }
+
+    pub fn set_system_audio_mode(
+        to: CecLogicalAddress,
+        status: CecSystemAudioStatus,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SetSystemAudioMode { status })
+    }
+
+    pub fn system_audio_mode_request(
+        to: CecLogicalAddress,
+        address: Option<CecPhysicalAddress>,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SystemAudioModeRequest { address })
+    }
+
+    pub fn system_audio_mode_status(
+        to: CecLogicalAddress,
+        status: CecSystemAudioStatus,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SystemAudioModeStatus { status })
+    }
+
+    pub fn set_audio_rate(
+        to: CecLogicalAddress,
+        rate: CecAudioRate,
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::SetAudioRate { rate })
+    }
+
+    /// `<Report Short Audio Descriptor>` with 1 to 4 descriptors
+    pub fn report_short_audio_descriptors(
+        to: CecLogicalAddress,
+        descriptors: &[[u8; 3]],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        let descriptors = array_vec(CecOpcode::ReportShortAudioDescriptors, descriptors)?;
+        Self::from_message(to, CecMessage::ReportShortAudioDescriptors { descriptors })
+    }
+
+    /// `<Request Short Audio Descriptor>` with 1 to 4 audio formats
+    pub fn request_short_audio_descriptors(
+        to: CecLogicalAddress,
+        formats: &[u8],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        let formats = array_vec(CecOpcode::RequestShortAudioDescriptors, formats)?;
+        Self::from_message(to, CecMessage::RequestShortAudioDescriptors { formats })
+    }
+
+    // Audio Return Channel Control
+
+    pub fn start_arc(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::StartArc)
+    }
+
+    pub fn report_arc_started(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::ReportArcStarted)
+    }
+
+    pub fn report_arc_ended(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::ReportArcEnded)
+    }
+
+    pub fn request_arc_start(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::RequestArcStart)
+    }
+
+    pub fn request_arc_end(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::RequestArcEnd)
+    }
+
+    pub fn end_arc(to: CecLogicalAddress) -> Result<CecCommand, CecCommandBuildError> {
+        Self::from_message(to, CecMessage::EndArc)
+    }
+
+    // Capability Discovery and Control
+
+    /// `<CDC Message>` with 1 to 12 bytes of CDC opcode and parameters
+    pub fn cdc(
+        initiator: CecPhysicalAddress,
+        data: &[u8],
+    ) -> Result<CecCommand, CecCommandBuildError> {
+        let data = datapacket(CecOpcode::Cdc, data, MAX_PARAMETERS_LENGTH - 2)?;
+        Self::from_message(
+            CecLogicalAddress::Unregistered,
+            CecMessage::Cdc { initiator, data },
+        )
+    }
+}
+
+#[cfg(test)]
+mod commands_tests {
+    use super::*;
+
+    #[test]
+    fn test_directed() {
+        let command = CecCommand::give_osd_name(CecLogicalAddress::Tv).unwrap();
+        assert_eq!(command.initiator, CecLogicalAddress::Unknown);
+        assert_eq!(command.destination, CecLogicalAddress::Tv);
+        assert_eq!(command.opcode, CecOpcode::GiveOsdName);
+        assert!(command.opcode_set);
+        assert!(command.parameters.0.is_empty());
+    }
+
+    #[test]
+    fn test_directed_rejects_broadcast() {
+        assert_eq!(
+            CecCommand::give_osd_name(CecLogicalAddress::Unregistered),
+            Err(CecCommandBuildError::BroadcastNotAllowed {
+                opcode: CecOpcode::GiveOsdName
+            })
+        );
+        assert_eq!(
+            CecCommand::feature_abort(
+                CecLogicalAddress::Unregistered,
+                CecOpcode::Play,
+                CecAbortReason::Refused
+            ),
+            Err(CecCommandBuildError::BroadcastNotAllowed {
+                opcode: CecOpcode::FeatureAbort
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_destination() {
+        assert_eq!(
+            CecCommand::standby(CecLogicalAddress::Unknown),
+            Err(CecCommandBuildError::InvalidDestination {
+                opcode: CecOpcode::Standby
+            })
+        );
+    }
+
+    #[test]
+    fn test_broadcast() {
+        let command = CecCommand::set_stream_path("1.2.0.0".parse().unwrap());
+        assert_eq!(command.destination, CecLogicalAddress::Unregistered);
+        assert_eq!(command.opcode, CecOpcode::SetStreamPath);
+        assert_eq!(command.parameters.0.as_slice(), &[0x12, 0x00]);
+    }
+
+    #[test]
+    fn test_broadcast_rejects_directed() {
+        assert_eq!(
+            CecCommand::from_message(
+                CecLogicalAddress::Tv,
+                CecMessage::ActiveSource {
+                    address: CecPhysicalAddress::ROOT
+                }
+            ),
+            Err(CecCommandBuildError::DirectedNotAllowed {
+                opcode: CecOpcode::ActiveSource
+            })
+        );
+    }
+
+    #[test]
+    fn test_either_addressing() {
+        assert!(CecCommand::standby(CecLogicalAddress::Unregistered).is_ok());
+        assert!(CecCommand::standby(CecLogicalAddress::Tv).is_ok());
+    }
+
+    #[test]
+    fn test_feature_abort() {
+        let command = CecCommand::feature_abort(
+            CecLogicalAddress::Playbackdevice1,
+            CecOpcode::Play,
+            CecAbortReason::Refused,
+        )
+        .unwrap();
+        assert_eq!(
+            command.message(),
+            Ok(CecMessage::FeatureAbort {
+                opcode: CecOpcode::Play,
+                reason: CecAbortReason::Refused
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_strings() {
+        assert_eq!(
+            CecCommand::set_osd_name(CecLogicalAddress::Tv, ""),
+            Err(CecCommandBuildError::InvalidParameter {
+                opcode: CecOpcode::SetOsdName
+            })
+        );
+        assert_eq!(
+            CecCommand::set_osd_name(CecLogicalAddress::Tv, "Fifteen chars!!"),
+            Err(CecCommandBuildError::InvalidParameter {
+                opcode: CecOpcode::SetOsdName
+            })
+        );
+        assert_eq!(
+            CecCommand::set_osd_name(CecLogicalAddress::Tv, "Äänentoisto"),
+            Err(CecCommandBuildError::InvalidParameter {
+                opcode: CecOpcode::SetOsdName
+            })
+        );
+        assert!(CecCommand::set_osd_name(CecLogicalAddress::Tv, "Fourteen chars").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        assert_eq!(
+            CecCommand::vendor_command_with_id(
+                CecLogicalAddress::Tv,
+                CecVendorId::PulseEight,
+                &[0; 12]
+            ),
+            Err(CecCommandBuildError::InvalidParameter {
+                opcode: CecOpcode::VendorCommandWithId
+            })
+        );
+        assert_eq!(
+            CecCommand::request_short_audio_descriptors(CecLogicalAddress::Audiosystem, &[]),
+            Err(CecCommandBuildError::InvalidParameter {
+                opcode: CecOpcode::RequestShortAudioDescriptors
+            })
+        );
+        assert!(CecCommand::vendor_command(CecLogicalAddress::Tv, &[0; 14]).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_timer() {
+        let timer = CecTimerInfo {
+            day_of_month: 0,
+            month: 1,
+            start_hour: 0,
+            start_minute: 0,
+            duration_hours: 1,
+            duration_minutes: 0,
+            recording_sequence: 0,
+        };
+        assert_eq!(
+            CecCommand::set_digital_timer(CecLogicalAddress::Recordingdevice1, timer, [0; 7]),
+            Err(CecCommandBuildError::InvalidParameter {
+                opcode: CecOpcode::SetDigitalTimer
+            })
+        );
+    }
+}
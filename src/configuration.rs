@@ -0,0 +1,436 @@
+use crate::{
+    first_n, from_c_chars, CecAdapterType, CecDeviceTypeVec, CecLogicalAddress,
+    CecLogicalAddresses, CecPhysicalAddress, CecUserControlCode, CecVersion, LibcecParameterType,
+};
+
+use libcec_sys::{
+    libcec_configuration, libcec_parameter, libcec_parameter_type_STRING, LIBCEC_OSD_NAME_SIZE,
+};
+use log::warn;
+use num_traits::ToPrimitive;
+use std::convert::TryFrom;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::time::Duration;
+
+/// Snapshot of the libcec configuration, as reported by libcec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CecConfiguration {
+    #[doc = "< the version of the client that is connecting"]
+    pub client_version: u32,
+    #[doc = "< the version of libCEC"]
+    pub server_version: u32,
+    #[doc = "< the device name to use on the CEC bus"]
+    pub device_name: String,
+    #[doc = "< the device type(s) to use on the CEC bus for libCEC"]
+    pub device_types: CecDeviceTypeVec,
+    #[doc = "< true when the physical address is autodetected"]
+    pub autodetect_address: bool,
+    #[doc = "< the physical address of the CEC adapter"]
+    pub physical_address: Option<CecPhysicalAddress>,
+    #[doc = "< the logical address of the device to which the adapter is connected"]
+    pub base_device: CecLogicalAddress,
+    #[doc = "< the HDMI port to which the adapter is connected"]
+    pub hdmi_port: u8,
+    #[doc = "< the vendor ID of the TV"]
+    pub tv_vendor: u32,
+    #[doc = "< list of devices to wake when initialising libCEC"]
+    pub wake_devices: CecLogicalAddresses,
+    #[doc = "< list of devices to power off when calling StandbyDevices() without any parameter"]
+    pub power_off_devices: CecLogicalAddresses,
+    #[doc = "< true to get the settings from the ROM"]
+    pub get_settings_from_rom: bool,
+    #[doc = "< make libCEC the active source on the bus when starting the player application"]
+    pub activate_source: bool,
+    #[doc = "< put this PC in standby mode when the TV is switched off"]
+    pub power_off_on_standby: bool,
+    #[doc = "< the current logical addresses"]
+    pub logical_addresses: CecLogicalAddresses,
+    #[doc = "< the firmware version of the adapter"]
+    pub firmware_version: u16,
+    #[doc = "< the menu language used by the client. 3 character ISO 639-2 country code"]
+    pub device_language: String,
+    #[doc = "< the build date of the firmware, in seconds since epoch"]
+    pub firmware_build_date: u32,
+    #[doc = "< won't allocate a CCECClient when starting the connection when set"]
+    pub monitor_only: bool,
+    #[doc = "< CEC spec version to use by libCEC"]
+    pub cec_version: CecVersion,
+    #[doc = "< type of the CEC adapter that we're connected to"]
+    pub adapter_type: CecAdapterType,
+    #[doc = "< key code that initiates combo keys"]
+    pub combo_key: CecUserControlCode,
+    #[doc = "< timeout until the combo key is sent as normal keypress"]
+    pub combo_key_timeout: Duration,
+    #[doc = "< rate at which buttons autorepeat. 0 means rely on CEC device"]
+    pub button_repeat_rate: Duration,
+    #[doc = "< duration after last update until a button is considered released"]
+    pub button_release_delay: Duration,
+    #[doc = "< prevent double taps within this timeout"]
+    pub double_tap_timeout: Duration,
+    #[doc = "< automatically wake an AVR when the source is activated"]
+    pub autowake_avr: bool,
+}
+
+fn logical_addresses(
+    field: &str,
+    addresses: libcec_sys::cec_logical_addresses,
+) -> CecLogicalAddresses {
+    CecLogicalAddresses::try_from(addresses).unwrap_or_else(|err| {
+        warn!(
+            "CecConfiguration: Could not convert {} ({:?}). Using empty addresses",
+            field, err
+        );
+        CecLogicalAddresses::default()
+    })
+}
+
+impl From<&libcec_configuration> for CecConfiguration {
+    /// Convert libcec configuration
+    ///
+    /// Values that cannot be represented with the rust enums are logged, and replaced with
+    /// the corresponding unknown value.
+    fn from(cfg: &libcec_configuration) -> CecConfiguration {
+        CecConfiguration {
+            client_version: cfg.clientVersion,
+            server_version: cfg.serverVersion,
+            device_name: from_c_chars(&cfg.strDeviceName),
+            device_types: cfg.deviceTypes.into(),
+            autodetect_address: cfg.bAutodetectAddress != 0,
+            physical_address: CecPhysicalAddress::new(cfg.iPhysicalAddress),
+            base_device: CecLogicalAddress::from_repr(cfg.baseDevice).unwrap_or_else(|| {
+                warn!(
+                    "CecConfiguration: Could not convert base device {}. Using Unknown",
+                    cfg.baseDevice
+                );
+                CecLogicalAddress::Unknown
+            }),
+            hdmi_port: cfg.iHDMIPort,
+            tv_vendor: cfg.tvVendor,
+            wake_devices: logical_addresses("wake devices", cfg.wakeDevices),
+            power_off_devices: logical_addresses("power off devices", cfg.powerOffDevices),
+            get_settings_from_rom: cfg.bGetSettingsFromROM != 0,
+            activate_source: cfg.bActivateSource != 0,
+            power_off_on_standby: cfg.bPowerOffOnStandby != 0,
+            logical_addresses: logical_addresses("logical addresses", cfg.logicalAddresses),
+            firmware_version: cfg.iFirmwareVersion,
+            device_language: from_c_chars(&cfg.strDeviceLanguage),
+            firmware_build_date: cfg.iFirmwareBuildDate,
+            monitor_only: cfg.bMonitorOnly != 0,
+            cec_version: CecVersion::from_repr(cfg.cecVersion).unwrap_or_else(|| {
+                warn!(
+                    "CecConfiguration: Could not convert CEC version {}. Using VersionUnknown",
+                    cfg.cecVersion
+                );
+                CecVersion::VersionUnknown
+            }),
+            adapter_type: CecAdapterType::from_repr(cfg.adapterType).unwrap_or_else(|| {
+                warn!(
+                    "CecConfiguration: Could not convert adapter type {}. Using Unknown",
+                    cfg.adapterType
+                );
+                CecAdapterType::Unknown
+            }),
+            combo_key: CecUserControlCode::from(cfg.comboKey),
+            combo_key_timeout: Duration::from_millis(cfg.iComboKeyTimeoutMs.into()),
+            button_repeat_rate: Duration::from_millis(cfg.iButtonRepeatRateMs.into()),
+            button_release_delay: Duration::from_millis(cfg.iButtonReleaseDelayMs.into()),
+            double_tap_timeout: Duration::from_millis(cfg.iDoubleTapTimeoutMs.into()),
+            autowake_avr: cfg.bAutoWakeAVR != 0,
+        }
+    }
+}
+
+/// Changes to apply to the configuration of an open connection, see
+/// `CecConnection::update_configuration`
+///
+/// Fields left to `None` are not changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CecConfigurationUpdate {
+    pub device_name: Option<String>,
+    pub device_types: Option<CecDeviceTypeVec>,
+    pub physical_address: Option<CecPhysicalAddress>,
+    pub base_device: Option<CecLogicalAddress>,
+    pub hdmi_port: Option<u8>,
+    pub tv_vendor: Option<u32>,
+    pub wake_devices: Option<CecLogicalAddresses>,
+    pub power_off_devices: Option<CecLogicalAddresses>,
+    pub activate_source: Option<bool>,
+    pub power_off_on_standby: Option<bool>,
+    pub device_language: Option<String>,
+    pub combo_key: Option<CecUserControlCode>,
+    pub combo_key_timeout: Option<Duration>,
+    pub button_repeat_rate: Option<Duration>,
+    pub button_release_delay: Option<Duration>,
+    pub double_tap_timeout: Option<Duration>,
+    pub autowake_avr: Option<bool>,
+}
+
+impl CecConfigurationUpdate {
+    /// Apply the changes to `config`
+    pub fn apply(&self, config: &mut CecConfiguration) {
+        if let Some(v) = &self.device_name {
+            config.device_name = v.clone();
+        }
+        if let Some(v) = &self.device_types {
+            config.device_types = v.clone();
+        }
+        if let Some(v) = self.physical_address {
+            config.physical_address = Some(v);
+        }
+        if let Some(v) = self.base_device {
+            config.base_device = v;
+        }
+        if let Some(v) = self.hdmi_port {
+            config.hdmi_port = v;
+        }
+        if let Some(v) = self.tv_vendor {
+            config.tv_vendor = v;
+        }
+        if let Some(v) = &self.wake_devices {
+            config.wake_devices = v.clone();
+        }
+        if let Some(v) = &self.power_off_devices {
+            config.power_off_devices = v.clone();
+        }
+        if let Some(v) = self.activate_source {
+            config.activate_source = v;
+        }
+        if let Some(v) = self.power_off_on_standby {
+            config.power_off_on_standby = v;
+        }
+        if let Some(v) = &self.device_language {
+            config.device_language = v.clone();
+        }
+        if let Some(v) = self.combo_key {
+            config.combo_key = v;
+        }
+        if let Some(v) = self.combo_key_timeout {
+            config.combo_key_timeout = v;
+        }
+        if let Some(v) = self.button_repeat_rate {
+            config.button_repeat_rate = v;
+        }
+        if let Some(v) = self.button_release_delay {
+            config.button_release_delay = v;
+        }
+        if let Some(v) = self.double_tap_timeout {
+            config.double_tap_timeout = v;
+        }
+        if let Some(v) = self.autowake_avr {
+            config.autowake_avr = v;
+        }
+    }
+
+    /// Apply the changes to libcec configuration `cfg`
+    pub(crate) fn apply_to_libcec(&self, cfg: &mut libcec_configuration) {
+        if let Some(v) = &self.device_name {
+            cfg.strDeviceName = first_n::<{ LIBCEC_OSD_NAME_SIZE as usize }>(v);
+        }
+        if let Some(v) = self.device_types.clone() {
+            cfg.deviceTypes = v.into();
+        }
+        if let Some(v) = self.physical_address {
+            cfg.iPhysicalAddress = v.into();
+        }
+        if let Some(v) = self.base_device {
+            cfg.baseDevice = v.repr();
+        }
+        if let Some(v) = self.hdmi_port {
+            cfg.iHDMIPort = v;
+        }
+        if let Some(v) = self.tv_vendor {
+            cfg.tvVendor = v;
+        }
+        if let Some(v) = self.wake_devices.clone() {
+            cfg.wakeDevices = v.into();
+        }
+        if let Some(v) = self.power_off_devices.clone() {
+            cfg.powerOffDevices = v.into();
+        }
+        if let Some(v) = self.activate_source {
+            cfg.bActivateSource = v.into();
+        }
+        if let Some(v) = self.power_off_on_standby {
+            cfg.bPowerOffOnStandby = v.into();
+        }
+        if let Some(v) = &self.device_language {
+            cfg.strDeviceLanguage = first_n::<3>(v);
+        }
+        if let Some(v) = self.combo_key {
+            cfg.comboKey = v.repr();
+        }
+        if let Some(v) = self.combo_key_timeout {
+            cfg.iComboKeyTimeoutMs = v.as_millis().to_u32().unwrap();
+        }
+        if let Some(v) = self.button_repeat_rate {
+            cfg.iButtonRepeatRateMs = v.as_millis().to_u32().unwrap();
+        }
+        if let Some(v) = self.button_release_delay {
+            cfg.iButtonReleaseDelayMs = v.as_millis().to_u32().unwrap();
+        }
+        if let Some(v) = self.double_tap_timeout {
+            cfg.iDoubleTapTimeoutMs = v.as_millis().to_u32().unwrap();
+        }
+        if let Some(v) = self.autowake_avr {
+            cfg.bAutoWakeAVR = v.into();
+        }
+    }
+}
+
+/// Parameter attached to a `LibcecAlert`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LibcecParameter {
+    String(String),
+    Unknown,
+}
+
+impl LibcecParameter {
+    pub fn parameter_type(&self) -> LibcecParameterType {
+        match self {
+            LibcecParameter::String(_) => LibcecParameterType::String,
+            LibcecParameter::Unknown => LibcecParameterType::Unkown,
+        }
+    }
+}
+
+impl From<libcec_parameter> for LibcecParameter {
+    fn from(parameter: libcec_parameter) -> LibcecParameter {
+        if parameter.paramType == libcec_parameter_type_STRING && !parameter.paramData.is_null() {
+            let data = unsafe { CStr::from_ptr(parameter.paramData as *const c_char) };
+            LibcecParameter::String(data.to_string_lossy().into_owned())
+        } else {
+            LibcecParameter::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod configuration_tests {
+    use super::*;
+    use crate::{CecDeviceType, KnownAndRegisteredCecLogicalAddress, KnownCecLogicalAddress};
+    use libcec_sys::{cec_logical_addresses, libcec_parameter_type_UNKOWN};
+    use std::collections::HashSet;
+    use std::ffi::CString;
+    use std::mem;
+
+    fn ffi_configuration() -> libcec_configuration {
+        let mut cfg = unsafe { mem::zeroed::<libcec_configuration>() };
+        cfg.serverVersion = 0x060000;
+        for (dst, src) in cfg.strDeviceName.iter_mut().zip(b"Kodi") {
+            *dst = *src as _;
+        }
+        cfg.deviceTypes.types = [CecDeviceType::Reserved.repr(); 5];
+        cfg.deviceTypes.types[0] = CecDeviceType::PlaybackDevice.repr();
+        cfg.iPhysicalAddress = 0x1000;
+        cfg.baseDevice = CecLogicalAddress::Tv.repr();
+        cfg.iHDMIPort = 1;
+        cfg.logicalAddresses = cec_logical_addresses {
+            primary: CecLogicalAddress::Playbackdevice1.repr(),
+            addresses: [0; 16],
+        };
+        cfg.logicalAddresses.addresses[CecLogicalAddress::Playbackdevice1.repr() as usize] = 1;
+        cfg.wakeDevices.primary = CecLogicalAddress::Unregistered.repr();
+        cfg.powerOffDevices.primary = CecLogicalAddress::Unregistered.repr();
+        for (dst, src) in cfg.strDeviceLanguage.iter_mut().zip(b"eng") {
+            *dst = *src as _;
+        }
+        cfg.cecVersion = CecVersion::Version14.repr();
+        cfg.adapterType = CecAdapterType::Linux.repr();
+        cfg.comboKey = CecUserControlCode::Stop.repr();
+        cfg.iComboKeyTimeoutMs = 1000;
+        cfg.bActivateSource = 1;
+        cfg
+    }
+
+    #[test]
+    fn test_from_ffi() {
+        let config = CecConfiguration::from(&ffi_configuration());
+        assert_eq!(config.server_version, 0x060000);
+        assert_eq!(config.device_name, "Kodi");
+        assert_eq!(
+            config.device_types,
+            CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice)
+        );
+        assert_eq!(config.physical_address, "1.0.0.0".parse().ok());
+        assert_eq!(config.base_device, CecLogicalAddress::Tv);
+        assert_eq!(config.hdmi_port, 1);
+        let primary = KnownCecLogicalAddress::new(CecLogicalAddress::Playbackdevice1).unwrap();
+        let mut addresses = HashSet::new();
+        addresses.insert(
+            KnownAndRegisteredCecLogicalAddress::new(CecLogicalAddress::Playbackdevice1).unwrap(),
+        );
+        assert_eq!(
+            config.logical_addresses,
+            CecLogicalAddresses::with_primary_and_addresses(&primary, &addresses).unwrap()
+        );
+        assert_eq!(config.wake_devices, CecLogicalAddresses::default());
+        assert_eq!(config.device_language, "eng");
+        assert_eq!(config.cec_version, CecVersion::Version14);
+        assert_eq!(config.adapter_type, CecAdapterType::Linux);
+        assert_eq!(config.combo_key, CecUserControlCode::Stop);
+        assert_eq!(config.combo_key_timeout, Duration::from_secs(1));
+        assert!(config.activate_source);
+        assert!(!config.monitor_only);
+    }
+
+    #[test]
+    fn test_from_ffi_invalid_values() {
+        let mut cfg = ffi_configuration();
+        cfg.iPhysicalAddress = 0xFFFF;
+        cfg.baseDevice = 42;
+        cfg.logicalAddresses.primary = 42;
+        let config = CecConfiguration::from(&cfg);
+        assert_eq!(config.physical_address, None);
+        assert_eq!(config.base_device, CecLogicalAddress::Unknown);
+        assert_eq!(config.logical_addresses, CecLogicalAddresses::default());
+    }
+
+    #[test]
+    fn test_update_round_trip() {
+        let update = CecConfigurationUpdate {
+            device_name: Some("Living room".into()),
+            physical_address: "2.1.0.0".parse().ok(),
+            activate_source: Some(false),
+            combo_key_timeout: Some(Duration::from_millis(500)),
+            ..CecConfigurationUpdate::default()
+        };
+        let mut cfg = ffi_configuration();
+        update.apply_to_libcec(&mut cfg);
+        let mut config = CecConfiguration::from(&ffi_configuration());
+        update.apply(&mut config);
+        assert_eq!(CecConfiguration::from(&cfg), config);
+        assert_eq!(config.device_name, "Living room");
+        assert_eq!(config.physical_address, "2.1.0.0".parse().ok());
+        assert!(!config.activate_source);
+        assert_eq!(config.combo_key_timeout, Duration::from_millis(500));
+        // Unchanged
+        assert_eq!(config.hdmi_port, 1);
+        assert_eq!(config.combo_key, CecUserControlCode::Stop);
+    }
+
+    #[test]
+    fn test_parameter_string() {
+        let data = CString::new("/dev/ttyACM0").unwrap();
+        let parameter = LibcecParameter::from(libcec_parameter {
+            paramType: libcec_parameter_type_STRING,
+            paramData: data.as_ptr() as *mut _,
+        });
+        assert_eq!(parameter, LibcecParameter::String("/dev/ttyACM0".into()));
+        assert_eq!(parameter.parameter_type(), LibcecParameterType::String);
+    }
+
+    #[test]
+    fn test_parameter_unknown() {
+        let parameter = LibcecParameter::from(libcec_parameter {
+            paramType: libcec_parameter_type_UNKOWN,
+            paramData: std::ptr::null_mut(),
+        });
+        assert_eq!(parameter, LibcecParameter::Unknown);
+        let parameter = LibcecParameter::from(libcec_parameter {
+            paramType: libcec_parameter_type_STRING,
+            paramData: std::ptr::null_mut(),
+        });
+        assert_eq!(parameter, LibcecParameter::Unknown);
+    }
+}
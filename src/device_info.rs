@@ -0,0 +1,98 @@
+use crate::{
+    CecConnection, CecLogicalAddress, CecMenuLanguage, CecPhysicalAddress, CecPowerStatus,
+    CecVendorId, CecVersion,
+};
+
+/// Identity and state of a device on the bus, see `CecConnection::scan_bus`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CecDeviceInfo {
+    pub logical_address: CecLogicalAddress,
+    #[doc = "None when the device did not report its physical address"]
+    pub physical_address: Option<CecPhysicalAddress>,
+    pub vendor_id: CecVendorId,
+    pub cec_version: CecVersion,
+    #[doc = "None when the device did not report its OSD name"]
+    pub osd_name: Option<String>,
+    #[doc = "None when the device did not report a menu language, usually only the TV does"]
+    pub menu_language: Option<CecMenuLanguage>,
+    pub power_status: CecPowerStatus,
+}
+
+impl CecConnection {
+    /// Query identity and state of the device at `address`
+    pub fn get_device_info(&self, address: CecLogicalAddress) -> CecDeviceInfo {
+        CecDeviceInfo {
+            logical_address: address,
+            physical_address: self.get_device_physical_address(address),
+            vendor_id: self.get_device_vendor_id(address),
+            cec_version: self.get_device_cec_version(address),
+            osd_name: self
+                .get_device_osd_name(address)
+                .ok()
+                .filter(|name| !name.is_empty()),
+            menu_language: self.get_device_menu_language(address).ok(),
+            power_status: self.get_device_power_status(address),
+        }
+    }
+
+    /// Rescan the bus, and query identity and state of all devices present
+    ///
+    /// Devices are queried one after another, which takes a few seconds on a real bus.
+    pub fn scan_bus(&self) -> Vec<CecDeviceInfo> {
+        self.rescan_devices();
+        self.get_active_devices()
+            .into_iter()
+            .map(|address| self.get_device_info(address))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod device_info_tests {
+    use super::*;
+    use crate::{
+        CecConnectionCfgBuilder, CecDeviceType, CecDeviceTypeVec, CecMockBus, CecSimDevice,
+    };
+
+    #[test]
+    fn test_scan_bus() {
+        let bus = CecMockBus::new();
+        bus.add_device(CecSimDevice::playback_device());
+        bus.add_device(CecSimDevice::tv());
+        let connection = bus
+            .open(
+                CecConnectionCfgBuilder::default()
+                    .device_name("test".into())
+                    .device_types(CecDeviceTypeVec::new(CecDeviceType::AudioSystem))
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        let devices = connection.scan_bus();
+        assert_eq!(
+            devices
+                .iter()
+                .map(|device| device.logical_address)
+                .collect::<Vec<_>>(),
+            vec![
+                CecLogicalAddress::Tv,
+                CecLogicalAddress::Playbackdevice1,
+                CecLogicalAddress::Audiosystem
+            ]
+        );
+        assert_eq!(
+            devices[0],
+            CecDeviceInfo {
+                logical_address: CecLogicalAddress::Tv,
+                physical_address: CecPhysicalAddress::from_ports([0, 0, 0, 0]),
+                vendor_id: CecVendorId::Samsung,
+                cec_version: CecVersion::Version14,
+                osd_name: Some("TV".into()),
+                menu_language: CecMenuLanguage::new("eng"),
+                power_status: CecPowerStatus::On,
+            }
+        );
+        assert_eq!(devices[1].osd_name, Some("Player".into()));
+        assert_eq!(devices[1].menu_language, None);
+    }
+}
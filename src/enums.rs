@@ -1,6 +1,47 @@
 use enum_repr::EnumRepr;
 use libcec_sys::*;
 
+/// Like `EnumRepr`, but with an `Other(raw)` variant for the values this crate does not
+/// know, e.g. CEC 2.0 or vendor specific codes. `From<raw>` maps every value, while
+/// `from_repr` returns `None` for the values that end up in `Other`.
+macro_rules! enum_with_other {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident($repr:ty) {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:path,)*
+        }
+    ) => {
+        $(#[$meta])*
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            #[doc = "value not known to this crate, see `From`"]
+            Other($repr),
+        }
+
+        impl $name {
+            pub fn repr(&self) -> $repr {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Other(value) => *value,
+                }
+            }
+
+            pub fn from_repr(value: $repr) -> Option<$name> {
+                match value {
+                    $($value => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl From<$repr> for $name {
+            fn from(value: $repr) -> Self {
+                $name::from_repr(value).unwrap_or($name::Other(value))
+            }
+        }
+    };
+}
+
 //
 // Enums
 //
@@ -372,97 +413,98 @@ pub enum CecBroadcastSystem {
     OtherSystem = libcec_sys::cec_broadcast_system_OTHER_SYSTEM,
 }
 
-#[EnumRepr(type = "cec_user_control_code")]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub enum CecUserControlCode {
-    Select = libcec_sys::cec_user_control_code_SELECT,
-    Up = libcec_sys::cec_user_control_code_UP,
-    Down = libcec_sys::cec_user_control_code_DOWN,
-    Left = libcec_sys::cec_user_control_code_LEFT,
-    Right = libcec_sys::cec_user_control_code_RIGHT,
-    RightUp = libcec_sys::cec_user_control_code_RIGHT_UP,
-    RightDown = libcec_sys::cec_user_control_code_RIGHT_DOWN,
-    LeftUp = libcec_sys::cec_user_control_code_LEFT_UP,
-    LeftDown = libcec_sys::cec_user_control_code_LEFT_DOWN,
-    RootMenu = libcec_sys::cec_user_control_code_ROOT_MENU,
-    SetupMenu = libcec_sys::cec_user_control_code_SETUP_MENU,
-    ContentsMenu = libcec_sys::cec_user_control_code_CONTENTS_MENU,
-    FavoriteMenu = libcec_sys::cec_user_control_code_FAVORITE_MENU,
-    Exit = libcec_sys::cec_user_control_code_EXIT,
-    TopMenu = libcec_sys::cec_user_control_code_TOP_MENU,
-    DvdMenu = libcec_sys::cec_user_control_code_DVD_MENU,
-    NumberEntryMode = libcec_sys::cec_user_control_code_NUMBER_ENTRY_MODE,
-    Number11 = libcec_sys::cec_user_control_code_NUMBER11,
-    Number12 = libcec_sys::cec_user_control_code_NUMBER12,
-    Number0 = libcec_sys::cec_user_control_code_NUMBER0,
-    Number1 = libcec_sys::cec_user_control_code_NUMBER1,
-    Number2 = libcec_sys::cec_user_control_code_NUMBER2,
-    Number3 = libcec_sys::cec_user_control_code_NUMBER3,
-    Number4 = libcec_sys::cec_user_control_code_NUMBER4,
-    Number5 = libcec_sys::cec_user_control_code_NUMBER5,
-    Number6 = libcec_sys::cec_user_control_code_NUMBER6,
-    Number7 = libcec_sys::cec_user_control_code_NUMBER7,
-    Number8 = libcec_sys::cec_user_control_code_NUMBER8,
-    Number9 = libcec_sys::cec_user_control_code_NUMBER9,
-    Dot = libcec_sys::cec_user_control_code_DOT,
-    Enter = libcec_sys::cec_user_control_code_ENTER,
-    Clear = libcec_sys::cec_user_control_code_CLEAR,
-    NextFavorite = libcec_sys::cec_user_control_code_NEXT_FAVORITE,
-    ChannelUp = libcec_sys::cec_user_control_code_CHANNEL_UP,
-    ChannelDown = libcec_sys::cec_user_control_code_CHANNEL_DOWN,
-    PreviousChannel = libcec_sys::cec_user_control_code_PREVIOUS_CHANNEL,
-    SoundSelect = libcec_sys::cec_user_control_code_SOUND_SELECT,
-    InputSelect = libcec_sys::cec_user_control_code_INPUT_SELECT,
-    DisplayInformation = libcec_sys::cec_user_control_code_DISPLAY_INFORMATION,
-    Help = libcec_sys::cec_user_control_code_HELP,
-    PageUp = libcec_sys::cec_user_control_code_PAGE_UP,
-    PageDown = libcec_sys::cec_user_control_code_PAGE_DOWN,
-    Power = libcec_sys::cec_user_control_code_POWER,
-    VolumeUp = libcec_sys::cec_user_control_code_VOLUME_UP,
-    VolumeDown = libcec_sys::cec_user_control_code_VOLUME_DOWN,
-    Mute = libcec_sys::cec_user_control_code_MUTE,
-    Play = libcec_sys::cec_user_control_code_PLAY,
-    Stop = libcec_sys::cec_user_control_code_STOP,
-    Pause = libcec_sys::cec_user_control_code_PAUSE,
-    Record = libcec_sys::cec_user_control_code_RECORD,
-    Rewind = libcec_sys::cec_user_control_code_REWIND,
-    FastForward = libcec_sys::cec_user_control_code_FAST_FORWARD,
-    Eject = libcec_sys::cec_user_control_code_EJECT,
-    Forward = libcec_sys::cec_user_control_code_FORWARD,
-    Backward = libcec_sys::cec_user_control_code_BACKWARD,
-    StopRecord = libcec_sys::cec_user_control_code_STOP_RECORD,
-    PauseRecord = libcec_sys::cec_user_control_code_PAUSE_RECORD,
-    Angle = libcec_sys::cec_user_control_code_ANGLE,
-    SubPicture = libcec_sys::cec_user_control_code_SUB_PICTURE,
-    VideoOnDemand = libcec_sys::cec_user_control_code_VIDEO_ON_DEMAND,
-    ElectronicProgramGuide = libcec_sys::cec_user_control_code_ELECTRONIC_PROGRAM_GUIDE,
-    TimerProgramming = libcec_sys::cec_user_control_code_TIMER_PROGRAMMING,
-    InitialConfiguration = libcec_sys::cec_user_control_code_INITIAL_CONFIGURATION,
-    SelectBroadcastType = libcec_sys::cec_user_control_code_SELECT_BROADCAST_TYPE,
-    SelectSoundPresentation = libcec_sys::cec_user_control_code_SELECT_SOUND_PRESENTATION,
-    PlayFunction = libcec_sys::cec_user_control_code_PLAY_FUNCTION,
-    PausePlayFunction = libcec_sys::cec_user_control_code_PAUSE_PLAY_FUNCTION,
-    RecordFunction = libcec_sys::cec_user_control_code_RECORD_FUNCTION,
-    PauseRecordFunction = libcec_sys::cec_user_control_code_PAUSE_RECORD_FUNCTION,
-    StopFunction = libcec_sys::cec_user_control_code_STOP_FUNCTION,
-    MuteFunction = libcec_sys::cec_user_control_code_MUTE_FUNCTION,
-    RestoreVolumeFunction = libcec_sys::cec_user_control_code_RESTORE_VOLUME_FUNCTION,
-    TuneFunction = libcec_sys::cec_user_control_code_TUNE_FUNCTION,
-    SelectMediaFunction = libcec_sys::cec_user_control_code_SELECT_MEDIA_FUNCTION,
-    SelectAvInputFunction = libcec_sys::cec_user_control_code_SELECT_AV_INPUT_FUNCTION,
-    SelectAudioInputFunction = libcec_sys::cec_user_control_code_SELECT_AUDIO_INPUT_FUNCTION,
-    PowerToggleFunction = libcec_sys::cec_user_control_code_POWER_TOGGLE_FUNCTION,
-    PowerOffFunction = libcec_sys::cec_user_control_code_POWER_OFF_FUNCTION,
-    PowerOnFunction = libcec_sys::cec_user_control_code_POWER_ON_FUNCTION,
-    F1Blue = libcec_sys::cec_user_control_code_F1_BLUE,
-    F2Red = libcec_sys::cec_user_control_code_F2_RED,
-    F3Green = libcec_sys::cec_user_control_code_F3_GREEN,
-    F4Yellow = libcec_sys::cec_user_control_code_F4_YELLOW,
-    F5 = libcec_sys::cec_user_control_code_F5,
-    Data = libcec_sys::cec_user_control_code_DATA,
-    AnReturn = libcec_sys::cec_user_control_code_AN_RETURN,
-    AnChannelsList = libcec_sys::cec_user_control_code_AN_CHANNELS_LIST,
-    Unknown = libcec_sys::cec_user_control_code_UNKNOWN,
+enum_with_other! {
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum CecUserControlCode(cec_user_control_code) {
+        Select = libcec_sys::cec_user_control_code_SELECT,
+        Up = libcec_sys::cec_user_control_code_UP,
+        Down = libcec_sys::cec_user_control_code_DOWN,
+        Left = libcec_sys::cec_user_control_code_LEFT,
+        Right = libcec_sys::cec_user_control_code_RIGHT,
+        RightUp = libcec_sys::cec_user_control_code_RIGHT_UP,
+        RightDown = libcec_sys::cec_user_control_code_RIGHT_DOWN,
+        LeftUp = libcec_sys::cec_user_control_code_LEFT_UP,
+        LeftDown = libcec_sys::cec_user_control_code_LEFT_DOWN,
+        RootMenu = libcec_sys::cec_user_control_code_ROOT_MENU,
+        SetupMenu = libcec_sys::cec_user_control_code_SETUP_MENU,
+        ContentsMenu = libcec_sys::cec_user_control_code_CONTENTS_MENU,
+        FavoriteMenu = libcec_sys::cec_user_control_code_FAVORITE_MENU,
+        Exit = libcec_sys::cec_user_control_code_EXIT,
+        TopMenu = libcec_sys::cec_user_control_code_TOP_MENU,
+        DvdMenu = libcec_sys::cec_user_control_code_DVD_MENU,
+        NumberEntryMode = libcec_sys::cec_user_control_code_NUMBER_ENTRY_MODE,
+        Number11 = libcec_sys::cec_user_control_code_NUMBER11,
+        Number12 = libcec_sys::cec_user_control_code_NUMBER12,
+        Number0 = libcec_sys::cec_user_control_code_NUMBER0,
+        Number1 = libcec_sys::cec_user_control_code_NUMBER1,
+        Number2 = libcec_sys::cec_user_control_code_NUMBER2,
+        Number3 = libcec_sys::cec_user_control_code_NUMBER3,
+        Number4 = libcec_sys::cec_user_control_code_NUMBER4,
+        Number5 = libcec_sys::cec_user_control_code_NUMBER5,
+        Number6 = libcec_sys::cec_user_control_code_NUMBER6,
+        Number7 = libcec_sys::cec_user_control_code_NUMBER7,
+        Number8 = libcec_sys::cec_user_control_code_NUMBER8,
+        Number9 = libcec_sys::cec_user_control_code_NUMBER9,
+        Dot = libcec_sys::cec_user_control_code_DOT,
+        Enter = libcec_sys::cec_user_control_code_ENTER,
+        Clear = libcec_sys::cec_user_control_code_CLEAR,
+        NextFavorite = libcec_sys::cec_user_control_code_NEXT_FAVORITE,
+        ChannelUp = libcec_sys::cec_user_control_code_CHANNEL_UP,
+        ChannelDown = libcec_sys::cec_user_control_code_CHANNEL_DOWN,
+        PreviousChannel = libcec_sys::cec_user_control_code_PREVIOUS_CHANNEL,
+        SoundSelect = libcec_sys::cec_user_control_code_SOUND_SELECT,
+        InputSelect = libcec_sys::cec_user_control_code_INPUT_SELECT,
+        DisplayInformation = libcec_sys::cec_user_control_code_DISPLAY_INFORMATION,
+        Help = libcec_sys::cec_user_control_code_HELP,
+        PageUp = libcec_sys::cec_user_control_code_PAGE_UP,
+        PageDown = libcec_sys::cec_user_control_code_PAGE_DOWN,
+        Power = libcec_sys::cec_user_control_code_POWER,
+        VolumeUp = libcec_sys::cec_user_control_code_VOLUME_UP,
+        VolumeDown = libcec_sys::cec_user_control_code_VOLUME_DOWN,
+        Mute = libcec_sys::cec_user_control_code_MUTE,
+        Play = libcec_sys::cec_user_control_code_PLAY,
+        Stop = libcec_sys::cec_user_control_code_STOP,
+        Pause = libcec_sys::cec_user_control_code_PAUSE,
+        Record = libcec_sys::cec_user_control_code_RECORD,
+        Rewind = libcec_sys::cec_user_control_code_REWIND,
+        FastForward = libcec_sys::cec_user_control_code_FAST_FORWARD,
+        Eject = libcec_sys::cec_user_control_code_EJECT,
+        Forward = libcec_sys::cec_user_control_code_FORWARD,
+        Backward = libcec_sys::cec_user_control_code_BACKWARD,
+        StopRecord = libcec_sys::cec_user_control_code_STOP_RECORD,
+        PauseRecord = libcec_sys::cec_user_control_code_PAUSE_RECORD,
+        Angle = libcec_sys::cec_user_control_code_ANGLE,
+        SubPicture = libcec_sys::cec_user_control_code_SUB_PICTURE,
+        VideoOnDemand = libcec_sys::cec_user_control_code_VIDEO_ON_DEMAND,
+        ElectronicProgramGuide = libcec_sys::cec_user_control_code_ELECTRONIC_PROGRAM_GUIDE,
+        TimerProgramming = libcec_sys::cec_user_control_code_TIMER_PROGRAMMING,
+        InitialConfiguration = libcec_sys::cec_user_control_code_INITIAL_CONFIGURATION,
+        SelectBroadcastType = libcec_sys::cec_user_control_code_SELECT_BROADCAST_TYPE,
+        SelectSoundPresentation = libcec_sys::cec_user_control_code_SELECT_SOUND_PRESENTATION,
+        PlayFunction = libcec_sys::cec_user_control_code_PLAY_FUNCTION,
+        PausePlayFunction = libcec_sys::cec_user_control_code_PAUSE_PLAY_FUNCTION,
+        RecordFunction = libcec_sys::cec_user_control_code_RECORD_FUNCTION,
+        PauseRecordFunction = libcec_sys::cec_user_control_code_PAUSE_RECORD_FUNCTION,
+        StopFunction = libcec_sys::cec_user_control_code_STOP_FUNCTION,
+        MuteFunction = libcec_sys::cec_user_control_code_MUTE_FUNCTION,
+        RestoreVolumeFunction = libcec_sys::cec_user_control_code_RESTORE_VOLUME_FUNCTION,
+        TuneFunction = libcec_sys::cec_user_control_code_TUNE_FUNCTION,
+        SelectMediaFunction = libcec_sys::cec_user_control_code_SELECT_MEDIA_FUNCTION,
+        SelectAvInputFunction = libcec_sys::cec_user_control_code_SELECT_AV_INPUT_FUNCTION,
+        SelectAudioInputFunction = libcec_sys::cec_user_control_code_SELECT_AUDIO_INPUT_FUNCTION,
+        PowerToggleFunction = libcec_sys::cec_user_control_code_POWER_TOGGLE_FUNCTION,
+        PowerOffFunction = libcec_sys::cec_user_control_code_POWER_OFF_FUNCTION,
+        PowerOnFunction = libcec_sys::cec_user_control_code_POWER_ON_FUNCTION,
+        F1Blue = libcec_sys::cec_user_control_code_F1_BLUE,
+        F2Red = libcec_sys::cec_user_control_code_F2_RED,
+        F3Green = libcec_sys::cec_user_control_code_F3_GREEN,
+        F4Yellow = libcec_sys::cec_user_control_code_F4_YELLOW,
+        F5 = libcec_sys::cec_user_control_code_F5,
+        Data = libcec_sys::cec_user_control_code_DATA,
+        AnReturn = libcec_sys::cec_user_control_code_AN_RETURN,
+        AnChannelsList = libcec_sys::cec_user_control_code_AN_CHANNELS_LIST,
+        Unknown = libcec_sys::cec_user_control_code_UNKNOWN,
+    }
 }
 
 #[EnumRepr(type = "cec_logical_address")]
@@ -486,81 +528,82 @@ pub enum CecLogicalAddress {
     Freeuse = libcec_sys::cec_logical_address_FREEUSE,
     Unregistered = libcec_sys::cec_logical_address_UNREGISTERED,
 }
-#[EnumRepr(type = "cec_opcode")]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub enum CecOpcode {
-    ActiveSource = libcec_sys::cec_opcode_ACTIVE_SOURCE,
-    ImageViewOn = libcec_sys::cec_opcode_IMAGE_VIEW_ON,
-    TextViewOn = libcec_sys::cec_opcode_TEXT_VIEW_ON,
-    InactiveSource = libcec_sys::cec_opcode_INACTIVE_SOURCE,
-    RequestActiveSource = libcec_sys::cec_opcode_REQUEST_ACTIVE_SOURCE,
-    RoutingChange = libcec_sys::cec_opcode_ROUTING_CHANGE,
-    RoutingInformation = libcec_sys::cec_opcode_ROUTING_INFORMATION,
-    SetStreamPath = libcec_sys::cec_opcode_SET_STREAM_PATH,
-    Standby = libcec_sys::cec_opcode_STANDBY,
-    RecordOff = libcec_sys::cec_opcode_RECORD_OFF,
-    RecordOn = libcec_sys::cec_opcode_RECORD_ON,
-    RecordStatus = libcec_sys::cec_opcode_RECORD_STATUS,
-    RecordTvScreen = libcec_sys::cec_opcode_RECORD_TV_SCREEN,
-    ClearAnalogueTimer = libcec_sys::cec_opcode_CLEAR_ANALOGUE_TIMER,
-    ClearDigitalTimer = libcec_sys::cec_opcode_CLEAR_DIGITAL_TIMER,
-    ClearExternalTimer = libcec_sys::cec_opcode_CLEAR_EXTERNAL_TIMER,
-    SetAnalogueTimer = libcec_sys::cec_opcode_SET_ANALOGUE_TIMER,
-    SetDigitalTimer = libcec_sys::cec_opcode_SET_DIGITAL_TIMER,
-    SetExternalTimer = libcec_sys::cec_opcode_SET_EXTERNAL_TIMER,
-    SetTimerProgramTitle = libcec_sys::cec_opcode_SET_TIMER_PROGRAM_TITLE,
-    TimerClearedStatus = libcec_sys::cec_opcode_TIMER_CLEARED_STATUS,
-    TimerStatus = libcec_sys::cec_opcode_TIMER_STATUS,
-    CecVersion = libcec_sys::cec_opcode_CEC_VERSION,
-    GetCecVersion = libcec_sys::cec_opcode_GET_CEC_VERSION,
-    GivePhysicalAddress = libcec_sys::cec_opcode_GIVE_PHYSICAL_ADDRESS,
-    GetMenuLanguage = libcec_sys::cec_opcode_GET_MENU_LANGUAGE,
-    ReportPhysicalAddress = libcec_sys::cec_opcode_REPORT_PHYSICAL_ADDRESS,
-    SetMenuLanguage = libcec_sys::cec_opcode_SET_MENU_LANGUAGE,
-    DeckControl = libcec_sys::cec_opcode_DECK_CONTROL,
-    DeckStatus = libcec_sys::cec_opcode_DECK_STATUS,
-    GiveDeckStatus = libcec_sys::cec_opcode_GIVE_DECK_STATUS,
-    Play = libcec_sys::cec_opcode_PLAY,
-    GiveTunerDeviceStatus = libcec_sys::cec_opcode_GIVE_TUNER_DEVICE_STATUS,
-    SelectAnalogueService = libcec_sys::cec_opcode_SELECT_ANALOGUE_SERVICE,
-    SelectDigitalService = libcec_sys::cec_opcode_SELECT_DIGITAL_SERVICE,
-    TunerDeviceStatus = libcec_sys::cec_opcode_TUNER_DEVICE_STATUS,
-    TunerStepDecrement = libcec_sys::cec_opcode_TUNER_STEP_DECREMENT,
-    TunerStepIncrement = libcec_sys::cec_opcode_TUNER_STEP_INCREMENT,
-    DeviceVendorId = libcec_sys::cec_opcode_DEVICE_VENDOR_ID,
-    GiveDeviceVendorId = libcec_sys::cec_opcode_GIVE_DEVICE_VENDOR_ID,
-    VendorCommand = libcec_sys::cec_opcode_VENDOR_COMMAND,
-    VendorCommandWithId = libcec_sys::cec_opcode_VENDOR_COMMAND_WITH_ID,
-    VendorRemoteButtonDown = libcec_sys::cec_opcode_VENDOR_REMOTE_BUTTON_DOWN,
-    VendorRemoteButtonUp = libcec_sys::cec_opcode_VENDOR_REMOTE_BUTTON_UP,
-    SetOsdString = libcec_sys::cec_opcode_SET_OSD_STRING,
-    GiveOsdName = libcec_sys::cec_opcode_GIVE_OSD_NAME,
-    SetOsdName = libcec_sys::cec_opcode_SET_OSD_NAME,
-    MenuRequest = libcec_sys::cec_opcode_MENU_REQUEST,
-    MenuStatus = libcec_sys::cec_opcode_MENU_STATUS,
-    UserControlPressed = libcec_sys::cec_opcode_USER_CONTROL_PRESSED,
-    UserControlRelease = libcec_sys::cec_opcode_USER_CONTROL_RELEASE,
-    GiveDevicePowerStatus = libcec_sys::cec_opcode_GIVE_DEVICE_POWER_STATUS,
-    ReportPowerStatus = libcec_sys::cec_opcode_REPORT_POWER_STATUS,
-    FeatureAbort = libcec_sys::cec_opcode_FEATURE_ABORT,
-    Abort = libcec_sys::cec_opcode_ABORT,
-    GiveAudioStatus = libcec_sys::cec_opcode_GIVE_AUDIO_STATUS,
-    GiveSystemAudioModeStatus = libcec_sys::cec_opcode_GIVE_SYSTEM_AUDIO_MODE_STATUS,
-    ReportAudioStatus = libcec_sys::cec_opcode_REPORT_AUDIO_STATUS,
-    SetSystemAudioMode = libcec_sys::cec_opcode_SET_SYSTEM_AUDIO_MODE,
-    SystemAudioModeRequest = libcec_sys::cec_opcode_SYSTEM_AUDIO_MODE_REQUEST,
-    SystemAudioModeStatus = libcec_sys::cec_opcode_SYSTEM_AUDIO_MODE_STATUS,
-    SetAudioRate = libcec_sys::cec_opcode_SET_AUDIO_RATE,
-    ReportShortAudioDescriptors = libcec_sys::cec_opcode_REPORT_SHORT_AUDIO_DESCRIPTORS,
-    RequestShortAudioDescriptors = libcec_sys::cec_opcode_REQUEST_SHORT_AUDIO_DESCRIPTORS,
-    StartArc = libcec_sys::cec_opcode_START_ARC,
-    ReportArcStarted = libcec_sys::cec_opcode_REPORT_ARC_STARTED,
-    ReportArcEnded = libcec_sys::cec_opcode_REPORT_ARC_ENDED,
-    RequestArcStart = libcec_sys::cec_opcode_REQUEST_ARC_START,
-    RequestArcEnd = libcec_sys::cec_opcode_REQUEST_ARC_END,
-    EndArc = libcec_sys::cec_opcode_END_ARC,
-    Cdc = libcec_sys::cec_opcode_CDC,
-    None = libcec_sys::cec_opcode_NONE,
+enum_with_other! {
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum CecOpcode(cec_opcode) {
+        ActiveSource = libcec_sys::cec_opcode_ACTIVE_SOURCE,
+        ImageViewOn = libcec_sys::cec_opcode_IMAGE_VIEW_ON,
+        TextViewOn = libcec_sys::cec_opcode_TEXT_VIEW_ON,
+        InactiveSource = libcec_sys::cec_opcode_INACTIVE_SOURCE,
+        RequestActiveSource = libcec_sys::cec_opcode_REQUEST_ACTIVE_SOURCE,
+        RoutingChange = libcec_sys::cec_opcode_ROUTING_CHANGE,
+        RoutingInformation = libcec_sys::cec_opcode_ROUTING_INFORMATION,
+        SetStreamPath = libcec_sys::cec_opcode_SET_STREAM_PATH,
+        Standby = libcec_sys::cec_opcode_STANDBY,
+        RecordOff = libcec_sys::cec_opcode_RECORD_OFF,
+        RecordOn = libcec_sys::cec_opcode_RECORD_ON,
+        RecordStatus = libcec_sys::cec_opcode_RECORD_STATUS,
+        RecordTvScreen = libcec_sys::cec_opcode_RECORD_TV_SCREEN,
+        ClearAnalogueTimer = libcec_sys::cec_opcode_CLEAR_ANALOGUE_TIMER,
+        ClearDigitalTimer = libcec_sys::cec_opcode_CLEAR_DIGITAL_TIMER,
+        ClearExternalTimer = libcec_sys::cec_opcode_CLEAR_EXTERNAL_TIMER,
+        SetAnalogueTimer = libcec_sys::cec_opcode_SET_ANALOGUE_TIMER,
+        SetDigitalTimer = libcec_sys::cec_opcode_SET_DIGITAL_TIMER,
+        SetExternalTimer = libcec_sys::cec_opcode_SET_EXTERNAL_TIMER,
+        SetTimerProgramTitle = libcec_sys::cec_opcode_SET_TIMER_PROGRAM_TITLE,
+        TimerClearedStatus = libcec_sys::cec_opcode_TIMER_CLEARED_STATUS,
+        TimerStatus = libcec_sys::cec_opcode_TIMER_STATUS,
+        CecVersion = libcec_sys::cec_opcode_CEC_VERSION,
+        GetCecVersion = libcec_sys::cec_opcode_GET_CEC_VERSION,
+        GivePhysicalAddress = libcec_sys::cec_opcode_GIVE_PHYSICAL_ADDRESS,
+        GetMenuLanguage = libcec_sys::cec_opcode_GET_MENU_LANGUAGE,
+        ReportPhysicalAddress = libcec_sys::cec_opcode_REPORT_PHYSICAL_ADDRESS,
+        SetMenuLanguage = libcec_sys::cec_opcode_SET_MENU_LANGUAGE,
+        DeckControl = libcec_sys::cec_opcode_DECK_CONTROL,
+        DeckStatus = libcec_sys::cec_opcode_DECK_STATUS,
+        GiveDeckStatus = libcec_sys::cec_opcode_GIVE_DECK_STATUS,
+        Play = libcec_sys::cec_opcode_PLAY,
+        GiveTunerDeviceStatus = libcec_sys::cec_opcode_GIVE_TUNER_DEVICE_STATUS,
+        SelectAnalogueService = libcec_sys::cec_opcode_SELECT_ANALOGUE_SERVICE,
+        SelectDigitalService = libcec_sys::cec_opcode_SELECT_DIGITAL_SERVICE,
+        TunerDeviceStatus = libcec_sys::cec_opcode_TUNER_DEVICE_STATUS,
+        TunerStepDecrement = libcec_sys::cec_opcode_TUNER_STEP_DECREMENT,
+        TunerStepIncrement = libcec_sys::cec_opcode_TUNER_STEP_INCREMENT,
+        DeviceVendorId = libcec_sys::cec_opcode_DEVICE_VENDOR_ID,
+        GiveDeviceVendorId = libcec_sys::cec_opcode_GIVE_DEVICE_VENDOR_ID,
+        VendorCommand = libcec_sys::cec_opcode_VENDOR_COMMAND,
+        VendorCommandWithId = libcec_sys::cec_opcode_VENDOR_COMMAND_WITH_ID,
+        VendorRemoteButtonDown = libcec_sys::cec_opcode_VENDOR_REMOTE_BUTTON_DOWN,
+        VendorRemoteButtonUp = libcec_sys::cec_opcode_VENDOR_REMOTE_BUTTON_UP,
+        SetOsdString = libcec_sys::cec_opcode_SET_OSD_STRING,
+        GiveOsdName = libcec_sys::cec_opcode_GIVE_OSD_NAME,
+        SetOsdName = libcec_sys::cec_opcode_SET_OSD_NAME,
+        MenuRequest = libcec_sys::cec_opcode_MENU_REQUEST,
+        MenuStatus = libcec_sys::cec_opcode_MENU_STATUS,
+        UserControlPressed = libcec_sys::cec_opcode_USER_CONTROL_PRESSED,
+        UserControlRelease = libcec_sys::cec_opcode_USER_CONTROL_RELEASE,
+        GiveDevicePowerStatus = libcec_sys::cec_opcode_GIVE_DEVICE_POWER_STATUS,
+        ReportPowerStatus = libcec_sys::cec_opcode_REPORT_POWER_STATUS,
+        FeatureAbort = libcec_sys::cec_opcode_FEATURE_ABORT,
+        Abort = libcec_sys::cec_opcode_ABORT,
+        GiveAudioStatus = libcec_sys::cec_opcode_GIVE_AUDIO_STATUS,
+        GiveSystemAudioModeStatus = libcec_sys::cec_opcode_GIVE_SYSTEM_AUDIO_MODE_STATUS,
+        ReportAudioStatus = libcec_sys::cec_opcode_REPORT_AUDIO_STATUS,
+        SetSystemAudioMode = libcec_sys::cec_opcode_SET_SYSTEM_AUDIO_MODE,
+        SystemAudioModeRequest = libcec_sys::cec_opcode_SYSTEM_AUDIO_MODE_REQUEST,
+        SystemAudioModeStatus = libcec_sys::cec_opcode_SYSTEM_AUDIO_MODE_STATUS,
+        SetAudioRate = libcec_sys::cec_opcode_SET_AUDIO_RATE,
+        ReportShortAudioDescriptors = libcec_sys::cec_opcode_REPORT_SHORT_AUDIO_DESCRIPTORS,
+        RequestShortAudioDescriptors = libcec_sys::cec_opcode_REQUEST_SHORT_AUDIO_DESCRIPTORS,
+        StartArc = libcec_sys::cec_opcode_START_ARC,
+        ReportArcStarted = libcec_sys::cec_opcode_REPORT_ARC_STARTED,
+        ReportArcEnded = libcec_sys::cec_opcode_REPORT_ARC_ENDED,
+        RequestArcStart = libcec_sys::cec_opcode_REQUEST_ARC_START,
+        RequestArcEnd = libcec_sys::cec_opcode_REQUEST_ARC_END,
+        EndArc = libcec_sys::cec_opcode_END_ARC,
+        Cdc = libcec_sys::cec_opcode_CDC,
+        None = libcec_sys::cec_opcode_NONE,
+    }
 }
 #[EnumRepr(type = "cec_log_level")]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -580,38 +623,39 @@ pub enum CecBusDeviceStatus {
     NotPresent = libcec_sys::cec_bus_device_status_NOT_PRESENT,
     HandledByLibcec = libcec_sys::cec_bus_device_status_HANDLED_BY_LIBCEC,
 }
-#[EnumRepr(type = "cec_vendor_id")]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub enum CecVendorId {
-    Toshiba = libcec_sys::cec_vendor_id_TOSHIBA,
-    Samsung = libcec_sys::cec_vendor_id_SAMSUNG,
-    Denon = libcec_sys::cec_vendor_id_DENON,
-    Marantz = libcec_sys::cec_vendor_id_MARANTZ,
-    Loewe = libcec_sys::cec_vendor_id_LOEWE,
-    Onkyo = libcec_sys::cec_vendor_id_ONKYO,
-    Medion = libcec_sys::cec_vendor_id_MEDION,
-    Toshiba2 = libcec_sys::cec_vendor_id_TOSHIBA2,
-    Apple = libcec_sys::cec_vendor_id_APPLE,
-    PulseEight = libcec_sys::cec_vendor_id_PULSE_EIGHT,
-    HarmanKardon2 = libcec_sys::cec_vendor_id_HARMAN_KARDON2,
-    Google = libcec_sys::cec_vendor_id_GOOGLE,
-    Akai = libcec_sys::cec_vendor_id_AKAI,
-    Aoc = libcec_sys::cec_vendor_id_AOC,
-    Panasonic = libcec_sys::cec_vendor_id_PANASONIC,
-    Philips = libcec_sys::cec_vendor_id_PHILIPS,
-    Daewoo = libcec_sys::cec_vendor_id_DAEWOO,
-    Yamaha = libcec_sys::cec_vendor_id_YAMAHA,
-    Grundig = libcec_sys::cec_vendor_id_GRUNDIG,
-    Pioneer = libcec_sys::cec_vendor_id_PIONEER,
-    Lg = libcec_sys::cec_vendor_id_LG,
-    Sharp = libcec_sys::cec_vendor_id_SHARP,
-    Sony = libcec_sys::cec_vendor_id_SONY,
-    Broadcom = libcec_sys::cec_vendor_id_BROADCOM,
-    Sharp2 = libcec_sys::cec_vendor_id_SHARP2,
-    Vizio = libcec_sys::cec_vendor_id_VIZIO,
-    Benq = libcec_sys::cec_vendor_id_BENQ,
-    HarmanKardon = libcec_sys::cec_vendor_id_HARMAN_KARDON,
-    Unknown = libcec_sys::cec_vendor_id_UNKNOWN,
+enum_with_other! {
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum CecVendorId(cec_vendor_id) {
+        Toshiba = libcec_sys::cec_vendor_id_TOSHIBA,
+        Samsung = libcec_sys::cec_vendor_id_SAMSUNG,
+        Denon = libcec_sys::cec_vendor_id_DENON,
+        Marantz = libcec_sys::cec_vendor_id_MARANTZ,
+        Loewe = libcec_sys::cec_vendor_id_LOEWE,
+        Onkyo = libcec_sys::cec_vendor_id_ONKYO,
+        Medion = libcec_sys::cec_vendor_id_MEDION,
+        Toshiba2 = libcec_sys::cec_vendor_id_TOSHIBA2,
+        Apple = libcec_sys::cec_vendor_id_APPLE,
+        PulseEight = libcec_sys::cec_vendor_id_PULSE_EIGHT,
+        HarmanKardon2 = libcec_sys::cec_vendor_id_HARMAN_KARDON2,
+        Google = libcec_sys::cec_vendor_id_GOOGLE,
+        Akai = libcec_sys::cec_vendor_id_AKAI,
+        Aoc = libcec_sys::cec_vendor_id_AOC,
+        Panasonic = libcec_sys::cec_vendor_id_PANASONIC,
+        Philips = libcec_sys::cec_vendor_id_PHILIPS,
+        Daewoo = libcec_sys::cec_vendor_id_DAEWOO,
+        Yamaha = libcec_sys::cec_vendor_id_YAMAHA,
+        Grundig = libcec_sys::cec_vendor_id_GRUNDIG,
+        Pioneer = libcec_sys::cec_vendor_id_PIONEER,
+        Lg = libcec_sys::cec_vendor_id_LG,
+        Sharp = libcec_sys::cec_vendor_id_SHARP,
+        Sony = libcec_sys::cec_vendor_id_SONY,
+        Broadcom = libcec_sys::cec_vendor_id_BROADCOM,
+        Sharp2 = libcec_sys::cec_vendor_id_SHARP2,
+        Vizio = libcec_sys::cec_vendor_id_VIZIO,
+        Benq = libcec_sys::cec_vendor_id_BENQ,
+        HarmanKardon = libcec_sys::cec_vendor_id_HARMAN_KARDON,
+        Unknown = libcec_sys::cec_vendor_id_UNKNOWN,
+    }
 }
 
 //
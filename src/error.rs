@@ -0,0 +1,133 @@
+use crate::{
+    CecCommandBuildError, CecConnectionResultError, CecMessageError, CecSwitchError,
+    NoCecDeviceTypeError, ParseCecPhysicalAddressError, TryFromCecAudioStatusError,
+    TryFromCecCommandError, TryFromCecLogMessageError, TryFromCecLogicalAddressesError,
+    UnregisteredCecLogicalAddress,
+};
+
+use std::fmt;
+
+/// Any error of this crate
+///
+/// Every error type of the crate converts into `Error` with `?`, the wrapped error
+/// carries the offending raw value where there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    AudioStatus(TryFromCecAudioStatusError),
+    NoDeviceType(NoCecDeviceTypeError),
+    PhysicalAddress(ParseCecPhysicalAddressError),
+    UnregisteredLogicalAddress(UnregisteredCecLogicalAddress),
+    Command(TryFromCecCommandError),
+    LogMessage(TryFromCecLogMessageError),
+    LogicalAddresses(TryFromCecLogicalAddressesError),
+    Message(CecMessageError),
+    CommandBuild(CecCommandBuildError),
+    Connection(CecConnectionResultError),
+    Switch(CecSwitchError),
+}
+
+impl Error {
+    fn inner(&self) -> &(dyn std::error::Error + 'static) {
+        match self {
+            Error::AudioStatus(err) => err,
+            Error::NoDeviceType(err) => err,
+            Error::PhysicalAddress(err) => err,
+            Error::UnregisteredLogicalAddress(err) => err,
+            Error::Command(err) => err,
+            Error::LogMessage(err) => err,
+            Error::LogicalAddresses(err) => err,
+            Error::Message(err) => err,
+            Error::CommandBuild(err) => err,
+            Error::Connection(err) => err,
+            Error::Switch(err) => err,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.inner(), f)
+    }
+}
+
+impl std::error::Error for Error {
+    // Display already shows the wrapped error, so skip it in the chain
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner().source()
+    }
+}
+
+macro_rules! impl_from_error {
+    ($($variant:ident($err:ty)),* $(,)?) => {
+        $(
+            impl From<$err> for Error {
+                fn from(err: $err) -> Self {
+                    Error::$variant(err)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_error!(
+    AudioStatus(TryFromCecAudioStatusError),
+    NoDeviceType(NoCecDeviceTypeError),
+    PhysicalAddress(ParseCecPhysicalAddressError),
+    UnregisteredLogicalAddress(UnregisteredCecLogicalAddress),
+    Command(TryFromCecCommandError),
+    LogMessage(TryFromCecLogMessageError),
+    LogicalAddresses(TryFromCecLogicalAddressesError),
+    Message(CecMessageError),
+    CommandBuild(CecCommandBuildError),
+    Connection(CecConnectionResultError),
+    Switch(CecSwitchError),
+);
+
+#[cfg(test)]
+mod error_tests {
+    use super::*;
+    use crate::{CecCommand, CecDeviceType, CecLogicalAddress, CecOpcode, CecPhysicalAddress};
+    use libcec_sys::cec_command;
+    use std::convert::TryFrom;
+    use std::error::Error as _;
+    use std::mem;
+
+    fn command(destination: i64) -> Result<CecCommand, Error> {
+        let mut command = unsafe { mem::zeroed::<cec_command>() };
+        command.destination = destination as _;
+        Ok(CecCommand::try_from(command)?)
+    }
+
+    #[test]
+    fn test_raw_value() {
+        assert_eq!(
+            command(20),
+            Err(Error::Command(TryFromCecCommandError::UnknownDestination(
+                20
+            )))
+        );
+        let device_type = CecDeviceType::try_from(CecLogicalAddress::Freeuse);
+        assert_eq!(
+            device_type.map_err(Error::from),
+            Err(Error::NoDeviceType(NoCecDeviceTypeError {
+                address: CecLogicalAddress::Freeuse
+            }))
+        );
+    }
+
+    #[test]
+    fn test_display_and_source() {
+        let err = Error::from(TryFromCecCommandError::UnknownDestination(20));
+        assert_eq!(err.to_string(), "unknown destination 20");
+        assert!(err.source().is_none());
+        let err = Error::from("1.2".parse::<CecPhysicalAddress>().unwrap_err());
+        assert_eq!(
+            err,
+            Error::PhysicalAddress(ParseCecPhysicalAddressError::InvalidFormat)
+        );
+        let err = Error::from(CecCommandBuildError::InvalidParameter {
+            opcode: CecOpcode::SetOsdName,
+        });
+        assert_eq!(err.to_string(), "SetOsdName parameter out of range");
+    }
+}
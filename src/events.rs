@@ -0,0 +1,120 @@
+use crate::{
+    CecCommand, CecConfiguration, CecConnection, CecConnectionCfg, CecConnectionResult,
+    CecKeypress, CecLogMessage, CecLogicalAddress, LibcecAlert, LibcecParameter,
+};
+
+use log::trace;
+use std::sync::mpsc::{sync_channel, Receiver};
+
+/// Event reported by libcec, as an alternative to the individual callbacks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CecEvent {
+    Keypress(CecKeypress),
+    Command(CecCommand),
+    LogMessage(CecLogMessage),
+    Alert(LibcecAlert, LibcecParameter),
+    SourceActivated {
+        address: CecLogicalAddress,
+        activated: bool,
+    },
+    ConfigurationChanged(Box<CecConfiguration>),
+}
+
+/// Receives all events, called from the libcec callback thread
+pub(crate) type FnEvent = dyn FnMut(CecEvent) + Send;
+
+fn event_receiver(capacity: usize) -> (Box<FnEvent>, Receiver<CecEvent>) {
+    let (sender, receiver) = sync_channel(capacity);
+    let event_callback = Box::new(move |event| {
+        if sender.send(event).is_err() {
+            trace!("CecEvent receiver dropped, discarding event");
+        }
+    });
+    (event_callback, receiver)
+}
+
+impl CecConnectionCfg {
+    /// Open connection, see `CecConnectionCfg::open`, and report events through a channel
+    ///
+    /// At most `capacity` events are buffered. When the buffer is full, libcec callback
+    /// thread blocks until the application receives events. Once the receiver is dropped,
+    /// further events are discarded. Callbacks set in this configuration are called as well.
+    ///
+    /// Processing events from the receiver, instead of in callbacks, allows calling
+    /// `CecConnection` methods without risk of deadlocking libcec.
+    pub fn open_with_event_receiver(
+        self,
+        capacity: usize,
+    ) -> CecConnectionResult<(CecConnection, Receiver<CecEvent>)> {
+        let (event_callback, receiver) = event_receiver(capacity);
+        let connection = self.open_with_event_callback(Some(event_callback))?;
+        Ok((connection, receiver))
+    }
+}
+
+#[cfg(test)]
+mod events_tests {
+    use super::*;
+    use crate::CecUserControlCode;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::TryRecvError;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    fn keypress(keycode: CecUserControlCode) -> CecEvent {
+        CecEvent::Keypress(CecKeypress {
+            keycode,
+            duration: Duration::from_millis(100),
+        })
+    }
+
+    #[test]
+    fn test_event_receiver() {
+        let (mut event_callback, receiver) = event_receiver(4);
+        event_callback(keypress(CecUserControlCode::Select));
+        event_callback(CecEvent::SourceActivated {
+            address: CecLogicalAddress::Playbackdevice1,
+            activated: true,
+        });
+        assert_eq!(receiver.recv(), Ok(keypress(CecUserControlCode::Select)));
+        assert_eq!(
+            receiver.recv(),
+            Ok(CecEvent::SourceActivated {
+                address: CecLogicalAddress::Playbackdevice1,
+                activated: true,
+            })
+        );
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+        drop(event_callback);
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_event_receiver_blocks_when_full() {
+        let (mut event_callback, receiver) = event_receiver(1);
+        let sent = Arc::new(AtomicBool::new(false));
+        let sender = {
+            let sent = Arc::clone(&sent);
+            thread::spawn(move || {
+                event_callback(keypress(CecUserControlCode::Up));
+                event_callback(keypress(CecUserControlCode::Down));
+                sent.store(true, Ordering::SeqCst);
+            })
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!sent.load(Ordering::SeqCst));
+        assert_eq!(receiver.recv(), Ok(keypress(CecUserControlCode::Up)));
+        assert_eq!(receiver.recv(), Ok(keypress(CecUserControlCode::Down)));
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn test_event_receiver_dropped() {
+        let (mut event_callback, receiver) = event_receiver(1);
+        drop(receiver);
+        // Must not panic nor block
+        event_callback(keypress(CecUserControlCode::Select));
+        event_callback(keypress(CecUserControlCode::Select));
+    }
+}
@@ -3,37 +3,65 @@ extern crate derive_builder;
 
 mod enums;
 pub use crate::enums::*;
+mod message;
+pub use crate::message::*;
+mod commands;
+pub use crate::commands::*;
+mod configuration;
+pub use crate::configuration::*;
+mod events;
+pub use crate::events::*;
+mod adapter;
+pub use crate::adapter::*;
+mod backend;
+use crate::backend::{CecBackend, LibcecBackend};
+mod mock;
+pub use crate::mock::*;
+mod sim;
+pub use crate::sim::*;
+mod device_info;
+pub use crate::device_info::*;
+mod topology;
+pub use crate::topology::*;
+mod registry;
+pub use crate::registry::*;
+mod error;
+pub use crate::error::*;
+mod routing;
+use crate::routing::CecCommandWatchers;
+pub use crate::routing::{CecSwitchError, CecSwitchTarget};
+mod supervisor;
+pub use crate::supervisor::*;
+#[cfg(feature = "async")]
+mod async_connection;
+#[cfg(feature = "async")]
+pub use crate::async_connection::*;
 
 #[cfg(all(not(abi4), not(abi5), not(abi6), not(abi7)))]
 compile_error!("BUG: libcec abi not detected");
 
-use log::{trace, warn};
+use log::{error, trace, warn};
 
-use std::{collections::HashSet, pin::Pin};
+use std::collections::HashSet;
 
 use arrayvec::ArrayVec;
 use libcec_sys::{
     cec_audio_status, cec_command, cec_datapacket, cec_device_type_list, cec_keypress,
-    cec_log_message, cec_logical_address, cec_logical_addresses, cec_power_status,
-    libcec_audio_get_status, libcec_audio_mute, libcec_audio_toggle_mute, libcec_audio_unmute,
-    libcec_clear_configuration, libcec_configuration, libcec_connection_t, libcec_destroy,
-    libcec_get_active_source, libcec_get_device_power_status, libcec_get_logical_addresses,
-    libcec_initialise, libcec_is_active_source, libcec_mute_audio, libcec_open,
-    libcec_power_on_devices, libcec_send_key_release, libcec_send_keypress,
-    libcec_set_active_source, libcec_set_deck_control_mode, libcec_set_deck_info,
-    libcec_set_inactive_view, libcec_set_logical_address, libcec_standby_devices,
-    libcec_switch_monitoring, libcec_transmit, libcec_volume_down, libcec_volume_up, ICECCallbacks,
-    LIBCEC_OSD_NAME_SIZE,
+    cec_log_level, cec_log_message, cec_logical_address, cec_logical_addresses, cec_menu_state,
+    libcec_alert, libcec_clear_configuration, libcec_configuration, libcec_parameter,
+    ICECCallbacks, LIBCEC_OSD_NAME_SIZE,
 };
 
 use num_traits::ToPrimitive;
+use std::any::Any;
 use std::cmp::min;
 use std::convert::{TryFrom, TryInto};
 use std::ffi::{CStr, CString};
-use std::mem::MaybeUninit;
 use std::os::raw::c_void;
-use std::ptr::addr_of_mut;
-use std::time::Duration;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 use std::{mem, result};
 
 use std::fmt;
@@ -90,6 +118,19 @@ fn first_n<const N: usize>(string: &str) -> [::std::os::raw::c_char; N] {
     data
 }
 
+/// Convert NUL terminated (or full) C character buffer to owned String
+///
+/// Invalid UTF-8 sequences are replaced with the replacement character.
+fn from_c_chars(data: &[::std::os::raw::c_char]) -> String {
+    let bytes: Vec<u8> = data
+        .iter()
+        .take_while(|c| **c != 0)
+        // c_char is either u8 or i8. We use simple casting to convert to u8 accordingly
+        .map(|c| *c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
 #[cfg(test)]
 mod util_tests {
     use super::*;
@@ -124,6 +165,19 @@ mod util_tests {
     fn test_first_0() {
         assert_eq!([] as [::std::os::raw::c_char; 0], first_n::<0>("sample"));
     }
+
+    #[test]
+    fn test_from_c_chars() {
+        assert_eq!("sample", from_c_chars(&first_n::<7>("sample")));
+        assert_eq!("sa", from_c_chars(&first_n::<7>("sa")));
+        assert_eq!("", from_c_chars(&first_n::<7>("")));
+    }
+
+    #[test]
+    fn test_from_c_chars_not_terminated() {
+        assert_eq!("sam", from_c_chars(&first_n::<3>("sample")));
+        assert_eq!("", from_c_chars(&first_n::<0>("sample")));
+    }
 }
 
 /// cec_audio_status which does not allow CEC_AUDIO_VOLUME_STATUS_UNKNOWN
@@ -162,6 +216,19 @@ pub enum TryFromCecAudioStatusError {
     Unknown,
 }
 
+impl fmt::Display for TryFromCecAudioStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryFromCecAudioStatusError::Reserved(value) => {
+                write!(f, "reserved audio volume status {}", value)
+            }
+            TryFromCecAudioStatusError::Unknown => write!(f, "audio volume status is unknown"),
+        }
+    }
+}
+
+impl std::error::Error for TryFromCecAudioStatusError {}
+
 impl TryFrom<u8> for KnownCecAudioStatus {
     type Error = TryFromCecAudioStatusError;
 
@@ -309,12 +376,30 @@ impl From<KnownCecLogicalAddress> for cec_logical_address {
     }
 }
 
+/// Logical address which does not map to a `CecDeviceType`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NoCecDeviceTypeError {
+    pub address: CecLogicalAddress,
+}
+
+impl fmt::Display for NoCecDeviceTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CecLogicalAddress::{:?} has no known CecDeviceType",
+            self.address
+        )
+    }
+}
+
+impl std::error::Error for NoCecDeviceTypeError {}
+
 /// Perform the conversion
 ///
 /// Try to create an owned `CecDeviceType` from a known logical address
 /// `KnownCecLogicalAddress`
 impl TryFrom<KnownCecLogicalAddress> for CecDeviceType {
-    type Error = &'static str;
+    type Error = NoCecDeviceTypeError;
 
     /// This implementation takes ownership of and consumes the
     /// `KnownCecLogicalAddress`.
@@ -327,7 +412,7 @@ impl TryFrom<KnownCecLogicalAddress> for CecDeviceType {
     ///
     /// * `address` - `KnownCecLogicalAddress` to use
     ///
-    /// Returns `Result<Error(&'static str)>` in the following cases
+    /// Returns `Err(NoCecDeviceTypeError)` in the following cases
     /// * when logical address is `Freeuse`
     /// * when logical address is `Unregistered`
     ///
@@ -350,7 +435,7 @@ impl TryFrom<KnownCecLogicalAddress> for CecDeviceType {
 /// Try to create an owned `CecDeviceType` from a known logical address
 /// reference `&KnownCecLogicalAddress`
 impl TryFrom<&KnownCecLogicalAddress> for CecDeviceType {
-    type Error = &'static str;
+    type Error = NoCecDeviceTypeError;
 
     ///
     /// **Note:** Some known logical addresses do not map cleanly into a known
@@ -361,7 +446,7 @@ impl TryFrom<&KnownCecLogicalAddress> for CecDeviceType {
     ///
     /// * `reference` - Reference to a `KnownCecLogicalAddress` to use
     ///
-    /// Returns `Result<Error(&'static str)>` in the following cases
+    /// Returns `Err(NoCecDeviceTypeError)` in the following cases
     /// * when logical address is `Freeuse`
     /// * when logical address is `Unregistered`
     ///
@@ -409,7 +494,7 @@ impl From<KnownAndRegisteredCecLogicalAddress> for cec_logical_address {
 /// Try to create an owned `CecDeviceType` from a known and registered logical
 /// address `KnownAndRegisteredCecLogicalAddress`
 impl TryFrom<KnownAndRegisteredCecLogicalAddress> for CecDeviceType {
-    type Error = &'static str;
+    type Error = NoCecDeviceTypeError;
 
     /// This implementation takes ownership of and consumes the
     /// `KnownAndRegisteredCecLogicalAddress`.
@@ -422,7 +507,7 @@ impl TryFrom<KnownAndRegisteredCecLogicalAddress> for CecDeviceType {
     ///
     /// * `address` - `KnownAndRegisteredLogicalAddress` to use
     ///
-    /// Returns `Result<Error(&'static str)>` in the following cases
+    /// Returns `Err(NoCecDeviceTypeError)` in the following cases
     /// * when logical address is `Freeuse`
     ///
     /// This logical address does not map cleanly into a known `CecDeviceType`.
@@ -445,7 +530,7 @@ impl TryFrom<KnownAndRegisteredCecLogicalAddress> for CecDeviceType {
 /// Try to create an owned `CecDeviceType` from a known and registered logical
 /// address `KnownAndRegisteredCecLogicalAddress`
 impl TryFrom<&KnownAndRegisteredCecLogicalAddress> for CecDeviceType {
-    type Error = &'static str;
+    type Error = NoCecDeviceTypeError;
 
     /// This implementation takes ownership of and consumes the
     /// `KnownAndRegisteredCecLogicalAddress`.
@@ -458,7 +543,7 @@ impl TryFrom<&KnownAndRegisteredCecLogicalAddress> for CecDeviceType {
     ///
     /// * `reference` - `&KnownAndRegisteredLogicalAddress` to use
     ///
-    /// Returns `Result<Error(&'static str)>` in the following cases
+    /// Returns `Err(NoCecDeviceTypeError)` in the following cases
     /// * when logical address is `Freeuse`
     ///
     /// This logical address does not map cleanly into a known `CecDeviceType`.
@@ -476,8 +561,338 @@ impl TryFrom<&KnownAndRegisteredCecLogicalAddress> for CecDeviceType {
     }
 }
 
+/// Physical address of a device in the HDMI topology, e.g. `1.2.0.0`
+///
+/// Each of the four nibbles identifies the HDMI input port at one level of the
+/// topology, starting from the TV (root) at `0.0.0.0`.
+///
+/// Does not allow the invalid address `0xFFFF` (`f.f.f.f`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CecPhysicalAddress(u16);
+
+impl CecPhysicalAddress {
+    /// Physical address of the root device (TV), `0.0.0.0`
+    pub const ROOT: CecPhysicalAddress = CecPhysicalAddress(0);
+
+    /// Maximum depth of the HDMI topology
+    pub const MAX_DEPTH: u8 = 4;
+
+    pub fn new(address: u16) -> Option<Self> {
+        match address {
+            0xFFFF => None,
+            valid_address => Some(Self(valid_address)),
+        }
+    }
+
+    /// Create physical address from HDMI ports, e.g. `[1, 2, 0, 0]` for `1.2.0.0`
+    ///
+    /// Returns `None` when some of the ports does not fit into a nibble, or
+    /// when the address would be invalid.
+    pub fn from_ports(ports: [u8; 4]) -> Option<Self> {
+        if ports.iter().any(|port| *port > 0xF) {
+            return None;
+        }
+        Self::new(
+            ports
+                .iter()
+                .fold(0u16, |address, port| (address << 4) | u16::from(*port)),
+        )
+    }
+
+    /// HDMI ports at each level, e.g. `[1, 2, 0, 0]` for `1.2.0.0`
+    pub fn ports(self) -> [u8; 4] {
+        [
+            (self.0 >> 12) as u8 & 0xF,
+            (self.0 >> 8) as u8 & 0xF,
+            (self.0 >> 4) as u8 & 0xF,
+            self.0 as u8 & 0xF,
+        ]
+    }
+
+    /// HDMI port used at given level (0-based), i.e. the input port of the
+    /// upstream device at that level
+    ///
+    /// Returns `None` when the level is beyond the depth of this address
+    pub fn port_at(self, level: u8) -> Option<u8> {
+        if level < self.depth() {
+            Some(self.ports()[level as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Depth in the HDMI topology. Root device (TV) has depth 0, devices
+    /// connected directly to the TV have depth 1, etc.
+    pub fn depth(self) -> u8 {
+        self.ports().iter().take_while(|port| **port != 0).count() as u8
+    }
+
+    pub fn is_root(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Physical address of the upstream device
+    ///
+    /// Returns `None` for the root device
+    pub fn parent(self) -> Option<Self> {
+        match self.depth() {
+            0 => None,
+            depth => {
+                let mut ports = self.ports();
+                ports[depth as usize - 1] = 0;
+                Self::from_ports(ports)
+            }
+        }
+    }
+
+    /// Physical address of the device connected to given HDMI input port
+    ///
+    /// Returns `None` when port is not within 1..=15, or when this address is
+    /// already at maximum depth
+    pub fn child(self, port: u8) -> Option<Self> {
+        let depth = self.depth();
+        if !(1..=0xF).contains(&port) || depth >= Self::MAX_DEPTH {
+            return None;
+        }
+        let mut ports = self.ports();
+        ports[depth as usize] = port;
+        Self::from_ports(ports)
+    }
+
+    /// Whether this device is upstream of `other`, i.e. `other` is connected
+    /// (directly or through other devices) to one of the inputs of this device
+    pub fn is_upstream_of(self, other: CecPhysicalAddress) -> bool {
+        let depth = self.depth();
+        depth < other.depth() && self.ports()[..depth as usize] == other.ports()[..depth as usize]
+    }
+
+    /// Whether this device is downstream of `other`. See `is_upstream_of`
+    pub fn is_downstream_of(self, other: CecPhysicalAddress) -> bool {
+        other.is_upstream_of(self)
+    }
+}
+
+impl From<CecPhysicalAddress> for u16 {
+    fn from(address: CecPhysicalAddress) -> Self {
+        address.0
+    }
+}
+
+impl fmt::Display for CecPhysicalAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.ports();
+        write!(f, "{:x}.{:x}.{:x}.{:x}", a, b, c, d)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseCecPhysicalAddressError {
+    InvalidFormat,
+    InvalidAddress,
+}
+
+impl fmt::Display for ParseCecPhysicalAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCecPhysicalAddressError::InvalidFormat => {
+                write!(f, "physical address is not in the form 1.2.0.0")
+            }
+            ParseCecPhysicalAddressError::InvalidAddress => {
+                write!(f, "physical address f.f.f.f is invalid")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseCecPhysicalAddressError {}
+
+impl FromStr for CecPhysicalAddress {
+    type Err = ParseCecPhysicalAddressError;
+
+    /// Parse physical address from dotted notation, e.g. `1.2.0.0`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ports = [0u8; 4];
+        let mut parts = s.split('.');
+        for port in ports.iter_mut() {
+            let part = parts
+                .next()
+                .ok_or(ParseCecPhysicalAddressError::InvalidFormat)?;
+            if part.len() != 1 {
+                return Err(ParseCecPhysicalAddressError::InvalidFormat);
+            }
+            *port = u8::from_str_radix(part, 16)
+                .map_err(|_| ParseCecPhysicalAddressError::InvalidFormat)?;
+        }
+        if parts.next().is_some() {
+            return Err(ParseCecPhysicalAddressError::InvalidFormat);
+        }
+        Self::from_ports(ports).ok_or(ParseCecPhysicalAddressError::InvalidAddress)
+    }
+}
+
+#[cfg(test)]
+mod physical_address_tests {
+    use super::*;
+
+    fn addr(s: &str) -> CecPhysicalAddress {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Some(0x1000), CecPhysicalAddress::new(0x1000).map(u16::from));
+        assert_eq!(Some(0), CecPhysicalAddress::new(0).map(u16::from));
+        assert_eq!(None, CecPhysicalAddress::new(0xFFFF));
+    }
+
+    #[test]
+    fn test_ports() {
+        let address = CecPhysicalAddress::new(0x12A0).unwrap();
+        assert_eq!([1, 2, 0xA, 0], address.ports());
+        assert_eq!(
+            Some(address),
+            CecPhysicalAddress::from_ports([1, 2, 0xA, 0])
+        );
+        assert_eq!(None, CecPhysicalAddress::from_ports([1, 0x10, 0, 0]));
+        assert_eq!(None, CecPhysicalAddress::from_ports([0xF; 4]));
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let address = addr("1.2.a.0");
+        assert_eq!(0x12A0, u16::from(address));
+        assert_eq!("1.2.a.0", address.to_string());
+        assert_eq!(addr("1.2.A.0"), address);
+        assert_eq!("0.0.0.0", CecPhysicalAddress::ROOT.to_string());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for invalid in [
+            "",
+            "1.0.0",
+            "1.0.0.0.0",
+            "1.0.0.g",
+            "10.0.0.0",
+            "1..0.0",
+            "1,0,0,0",
+        ] {
+            assert_eq!(
+                Err(ParseCecPhysicalAddressError::InvalidFormat),
+                invalid.parse::<CecPhysicalAddress>(),
+                "{}",
+                invalid
+            );
+        }
+        assert_eq!(
+            Err(ParseCecPhysicalAddressError::InvalidAddress),
+            "f.f.f.f".parse::<CecPhysicalAddress>()
+        );
+    }
+
+    #[test]
+    fn test_depth_and_port_at() {
+        assert_eq!(0, CecPhysicalAddress::ROOT.depth());
+        assert!(CecPhysicalAddress::ROOT.is_root());
+        assert_eq!(1, addr("3.0.0.0").depth());
+        assert_eq!(4, addr("1.2.3.4").depth());
+
+        let address = addr("1.2.0.0");
+        assert!(!address.is_root());
+        assert_eq!(2, address.depth());
+        assert_eq!(Some(1), address.port_at(0));
+        assert_eq!(Some(2), address.port_at(1));
+        assert_eq!(None, address.port_at(2));
+        assert_eq!(None, CecPhysicalAddress::ROOT.port_at(0));
+    }
+
+    #[test]
+    fn test_parent_and_child() {
+        assert_eq!(None, CecPhysicalAddress::ROOT.parent());
+        assert_eq!(Some(CecPhysicalAddress::ROOT), addr("2.0.0.0").parent());
+        assert_eq!(Some(addr("1.2.0.0")), addr("1.2.3.0").parent());
+        assert_eq!(Some(addr("1.2.3.0")), addr("1.2.3.4").parent());
+
+        assert_eq!(Some(addr("4.0.0.0")), CecPhysicalAddress::ROOT.child(4));
+        assert_eq!(Some(addr("1.2.f.0")), addr("1.2.0.0").child(0xF));
+        assert_eq!(None, addr("1.2.0.0").child(0));
+        assert_eq!(None, addr("1.2.0.0").child(0x10));
+        assert_eq!(None, addr("1.2.3.4").child(1));
+    }
+
+    #[test]
+    fn test_upstream() {
+        let tv = CecPhysicalAddress::ROOT;
+        let avr = addr("1.0.0.0");
+        let player = addr("1.3.0.0");
+        let other = addr("2.0.0.0");
+        assert!(tv.is_upstream_of(avr));
+        assert!(tv.is_upstream_of(player));
+        assert!(avr.is_upstream_of(player));
+        assert!(player.is_downstream_of(avr));
+        assert!(!player.is_upstream_of(avr));
+        assert!(!avr.is_upstream_of(avr));
+        assert!(!avr.is_upstream_of(other));
+        assert!(!other.is_upstream_of(player));
+    }
+}
+
+/// Menu language of a device, as 3 character ISO 639-2 code (e.g. `eng`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CecMenuLanguage([u8; 3]);
+
+impl CecMenuLanguage {
+    /// Create CecMenuLanguage from ISO 639-2 code
+    ///
+    /// Returns `None` when `language` is not exactly 3 ASCII letters
+    pub fn new(language: &str) -> Option<Self> {
+        let bytes = language.as_bytes();
+        if bytes.len() == 3 && bytes.iter().all(u8::is_ascii_alphabetic) {
+            Some(Self([bytes[0], bytes[1], bytes[2]]))
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Cannot panic, contents are validated to be ASCII in new()
+        std::str::from_utf8(&self.0).unwrap()
+    }
+}
+
+impl fmt::Display for CecMenuLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod menu_language_tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_language() {
+        let language = CecMenuLanguage::new("eng").unwrap();
+        assert_eq!("eng", language.as_str());
+        assert_eq!("eng", format!("{}", language));
+        assert_eq!(None, CecMenuLanguage::new("en"));
+        assert_eq!(None, CecMenuLanguage::new("engl"));
+        assert_eq!(None, CecMenuLanguage::new("e1g"));
+        assert_eq!(None, CecMenuLanguage::new("äng"));
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct UnregisteredCecLogicalAddress {}
+
+impl fmt::Display for UnregisteredCecLogicalAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "logical address is Unregistered")
+    }
+}
+
+impl std::error::Error for UnregisteredCecLogicalAddress {}
+
 impl TryFrom<KnownCecLogicalAddress> for KnownAndRegisteredCecLogicalAddress {
     type Error = UnregisteredCecLogicalAddress;
 
@@ -593,7 +1008,7 @@ mod datapacket_tests {
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CecCommand {
     #[doc = "< the logical address of the initiator of this message"]
     pub initiator: CecLogicalAddress,
@@ -630,21 +1045,35 @@ impl From<CecCommand> for cec_command {
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum TryFromCecCommandError {
-    UnknownOpcode,
-    UnknownInitiator,
-    UnknownDestination,
+    UnknownInitiator(cec_logical_address),
+    UnknownDestination(cec_logical_address),
+}
+
+impl fmt::Display for TryFromCecCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryFromCecCommandError::UnknownInitiator(address) => {
+                write!(f, "unknown initiator {}", address)
+            }
+            TryFromCecCommandError::UnknownDestination(address) => {
+                write!(f, "unknown destination {}", address)
+            }
+        }
+    }
 }
 
+impl std::error::Error for TryFromCecCommandError {}
+
 impl core::convert::TryFrom<cec_command> for CecCommand {
     type Error = TryFromCecCommandError;
 
     fn try_from(command: cec_command) -> std::result::Result<Self, Self::Error> {
-        let opcode =
-            CecOpcode::from_repr(command.opcode).ok_or(TryFromCecCommandError::UnknownOpcode)?;
+        let opcode = CecOpcode::from(command.opcode);
         let initiator = CecLogicalAddress::from_repr(command.initiator)
-            .ok_or(TryFromCecCommandError::UnknownInitiator)?;
-        let destination = CecLogicalAddress::from_repr(command.destination)
-            .ok_or(TryFromCecCommandError::UnknownDestination)?;
+            .ok_or(TryFromCecCommandError::UnknownInitiator(command.initiator))?;
+        let destination = CecLogicalAddress::from_repr(command.destination).ok_or(
+            TryFromCecCommandError::UnknownDestination(command.destination),
+        )?;
         let parameters = command.parameters.into();
         let transmit_timeout = Duration::from_millis(if command.transmit_timeout < 0 {
             0
@@ -756,17 +1185,49 @@ mod command_tests {
             },
         )
     }
+
+    #[test]
+    fn test_from_ffi_unknown_opcode() {
+        let mut ffi_command: cec_command =
+            CecCommand::active_source(CecPhysicalAddress::from_ports([1, 0, 0, 0]).unwrap()).into();
+        ffi_command.opcode = 0xA7;
+        let command = CecCommand::try_from(ffi_command).unwrap();
+        assert_eq!(command.opcode, CecOpcode::Other(0xA7));
+        assert_eq!(command.opcode.repr(), 0xA7);
+        assert_eq!(cec_command::from(command).opcode, 0xA7);
+        assert_eq!(CecOpcode::from(0x82), CecOpcode::ActiveSource);
+        assert_eq!(CecOpcode::from_repr(0xA7), None);
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum TryFromCecLogMessageError {
     MessageParseError,
-    LogLevelParseError,
-    TimestampParseError,
+    LogLevelParseError(cec_log_level),
+    TimestampParseError(i64),
     UnknownLogLevel,
 }
 
-#[derive(Clone)]
+impl fmt::Display for TryFromCecLogMessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryFromCecLogMessageError::MessageParseError => {
+                write!(f, "log message is not valid UTF-8")
+            }
+            TryFromCecLogMessageError::LogLevelParseError(level) => {
+                write!(f, "unknown log level {}", level)
+            }
+            TryFromCecLogMessageError::TimestampParseError(time) => {
+                write!(f, "invalid log timestamp {}", time)
+            }
+            TryFromCecLogMessageError::UnknownLogLevel => write!(f, "unknown log level"),
+        }
+    }
+}
+
+impl std::error::Error for TryFromCecLogMessageError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CecLogMessage {
     #[doc = "the actual message"]
     pub message: String,
@@ -785,12 +1246,13 @@ impl core::convert::TryFrom<cec_log_message> for CecLogMessage {
             .to_str()
             .map_err(|_| TryFromCecLogMessageError::MessageParseError)?
             .to_owned();
-        let level = CecLogLevel::from_repr(log_message.level)
-            .ok_or(TryFromCecLogMessageError::LogLevelParseError)?;
+        let level = CecLogLevel::from_repr(log_message.level).ok_or(
+            TryFromCecLogMessageError::LogLevelParseError(log_message.level),
+        )?;
         let time = log_message
             .time
             .try_into()
-            .map_err(|_| TryFromCecLogMessageError::TimestampParseError)?;
+            .map_err(|_| TryFromCecLogMessageError::TimestampParseError(log_message.time))?;
 
         Ok(CecLogMessage {
             message,
@@ -866,14 +1328,30 @@ impl CecLogicalAddresses {
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum TryFromCecLogicalAddressesError {
     UnknownPrimaryAddress,
-    InvalidPrimaryAddress,
+    InvalidPrimaryAddress(cec_logical_address),
+}
+
+impl fmt::Display for TryFromCecLogicalAddressesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryFromCecLogicalAddressesError::UnknownPrimaryAddress => {
+                write!(f, "primary logical address is Unknown")
+            }
+            TryFromCecLogicalAddressesError::InvalidPrimaryAddress(address) => {
+                write!(f, "invalid primary logical address {}", address)
+            }
+        }
+    }
 }
 
+impl std::error::Error for TryFromCecLogicalAddressesError {}
+
 impl TryFrom<cec_logical_addresses> for CecLogicalAddresses {
     type Error = TryFromCecLogicalAddressesError;
     fn try_from(addresses: cec_logical_addresses) -> Result<Self, Self::Error> {
-        let primary = CecLogicalAddress::from_repr(addresses.primary)
-            .ok_or(TryFromCecLogicalAddressesError::InvalidPrimaryAddress)?;
+        let primary = CecLogicalAddress::from_repr(addresses.primary).ok_or(
+            TryFromCecLogicalAddressesError::InvalidPrimaryAddress(addresses.primary),
+        )?;
         let primary = KnownCecLogicalAddress::new(primary)
             .ok_or(TryFromCecLogicalAddressesError::UnknownPrimaryAddress)?;
 
@@ -1097,17 +1575,22 @@ mod logical_addresses_tests {
             ),
             (
                 CecLogicalAddress::Freeuse,
-                Err("CecLogicalAddress::Freeuse has no known CecDeviceType"),
+                Err(NoCecDeviceTypeError {
+                    address: CecLogicalAddress::Freeuse,
+                }),
             ),
             (
                 CecLogicalAddress::Unregistered,
-                Err("CecLogicalAddress::Unregistered has no known CecDeviceType"),
+                Err(NoCecDeviceTypeError {
+                    address: CecLogicalAddress::Unregistered,
+                }),
             ),
             (
                 CecLogicalAddress::Unknown,
-                // Test should trigger on Err() message mismatch in case of refactoring regressions.
                 // This should not be possible because KnownCecLogicalAddress can't be created from Unknown
-                Err("It should not be possible to create a `KnownCecLogicalAddress` from `CecLogicalAddress::Unknown`"),
+                Err(NoCecDeviceTypeError {
+                    address: CecLogicalAddress::Unknown,
+                }),
             ),
         ];
 
@@ -1142,20 +1625,12 @@ pub struct CecKeypress {
     pub duration: Duration,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum TryFromCecKeyPressError {
-    UnknownKeycode,
-}
-
-impl core::convert::TryFrom<cec_keypress> for CecKeypress {
-    type Error = TryFromCecKeyPressError;
-    fn try_from(keypress: cec_keypress) -> std::result::Result<Self, Self::Error> {
-        let keycode = CecUserControlCode::from_repr(keypress.keycode)
-            .ok_or(TryFromCecKeyPressError::UnknownKeycode)?;
-        Ok(CecKeypress {
-            keycode,
+impl From<cec_keypress> for CecKeypress {
+    fn from(keypress: cec_keypress) -> Self {
+        CecKeypress {
+            keycode: keypress.keycode.into(),
             duration: Duration::from_millis(keypress.duration.into()),
-        })
+        }
     }
 }
 
@@ -1171,20 +1646,20 @@ mod keypress_tests {
             keycode: cec_user_control_code_UP,
             duration: 300,
         }
-        .try_into()
-        .unwrap();
+        .into();
         assert_eq!(keypress.keycode, CecUserControlCode::Up);
         assert_eq!(keypress.duration, Duration::from_millis(300));
     }
 
     #[test]
     fn test_keypress_from_ffi_unknown_code() {
-        let keypress: Result<CecKeypress, TryFromCecKeyPressError> = cec_keypress {
+        let keypress: CecKeypress = cec_keypress {
             keycode: 666,
             duration: 300,
         }
-        .try_into();
-        assert_eq!(keypress, Err(TryFromCecKeyPressError::UnknownKeycode));
+        .into();
+        assert_eq!(keypress.keycode, CecUserControlCode::Other(666));
+        assert_eq!(keypress.keycode.repr(), 666);
     }
 }
 
@@ -1192,7 +1667,7 @@ mod keypress_tests {
 ///
 /// Try to create an owned `CecDeviceType` from a logical address `CecLogicalAddress`
 impl TryFrom<CecLogicalAddress> for CecDeviceType {
-    type Error = &'static str;
+    type Error = NoCecDeviceTypeError;
 
     // Note: Match arms are in ascending order of Logical Address numbers
     // (according to CEC specification).  Typically the most common ones are used first.
@@ -1206,7 +1681,7 @@ impl TryFrom<CecLogicalAddress> for CecDeviceType {
     ///
     /// * `value` - Logical address to use
     ///
-    /// Returns `Result<Error(&'static str)>` in the following cases
+    /// Returns `Err(NoCecDeviceTypeError)` in the following cases
     /// * when logical address is `Freeuse`
     /// * when logical address is `Unregistered`
     /// * when logical address is `Unknown`
@@ -1231,17 +1706,19 @@ impl TryFrom<CecLogicalAddress> for CecDeviceType {
             CecLogicalAddress::Playbackdevice3 => Ok(CecDeviceType::PlaybackDevice),
             CecLogicalAddress::Reserved1 => Ok(CecDeviceType::Reserved),
             CecLogicalAddress::Reserved2 => Ok(CecDeviceType::Reserved),
-            CecLogicalAddress::Freeuse => {
-                Err("CecLogicalAddress::Freeuse has no known CecDeviceType")
-            }
+            CecLogicalAddress::Freeuse => Err(NoCecDeviceTypeError {
+                address: CecLogicalAddress::Freeuse,
+            }),
             CecLogicalAddress::Unregistered => {
                 // uses the broadcast address (15 = 0xf)
-                Err("CecLogicalAddress::Unregistered has no known CecDeviceType")
+                Err(NoCecDeviceTypeError {
+                    address: CecLogicalAddress::Unregistered,
+                })
             }
             // Unknown = -1
-            CecLogicalAddress::Unknown => {
-                Err("CecLogicalAddress::Unknown has no known CecDeviceType")
-            }
+            CecLogicalAddress::Unknown => Err(NoCecDeviceTypeError {
+                address: CecLogicalAddress::Unknown,
+            }),
         }
     }
 }
@@ -1250,7 +1727,7 @@ impl TryFrom<CecLogicalAddress> for CecDeviceType {
 ///
 /// Try to create an owned `CecDeviceType` from a logical address reference `&CecLogicalAddress`
 impl TryFrom<&CecLogicalAddress> for CecDeviceType {
-    type Error = &'static str;
+    type Error = NoCecDeviceTypeError;
 
     // Note: Match arms are in ascending order of Logical Address numbers
     // (according to CEC specification).  Typically the most common ones are used first.
@@ -1264,7 +1741,7 @@ impl TryFrom<&CecLogicalAddress> for CecDeviceType {
     ///
     /// * `reference` - Reference to a logical address to use
     ///
-    /// Returns `Result<Error(&'static str)>` in the following cases
+    /// Returns `Err(NoCecDeviceTypeError)` in the following cases
     /// * when logical address is `Freeuse`
     /// * when logical address is `Unregistered`
     /// * when logical address is `Unknown`
@@ -1289,17 +1766,19 @@ impl TryFrom<&CecLogicalAddress> for CecDeviceType {
             CecLogicalAddress::Playbackdevice3 => Ok(CecDeviceType::PlaybackDevice),
             CecLogicalAddress::Reserved1 => Ok(CecDeviceType::Reserved),
             CecLogicalAddress::Reserved2 => Ok(CecDeviceType::Reserved),
-            CecLogicalAddress::Freeuse => {
-                Err("CecLogicalAddress::Freeuse has no known CecDeviceType")
-            }
+            CecLogicalAddress::Freeuse => Err(NoCecDeviceTypeError {
+                address: CecLogicalAddress::Freeuse,
+            }),
             CecLogicalAddress::Unregistered => {
                 // uses the broadcast address (15 = 0xf)
-                Err("CecLogicalAddress::Unregistered has no known CecDeviceType")
+                Err(NoCecDeviceTypeError {
+                    address: CecLogicalAddress::Unregistered,
+                })
             }
             // Unknown = -1
-            CecLogicalAddress::Unknown => {
-                Err("CecLogicalAddress::Unknown has no known CecDeviceType")
-            }
+            CecLogicalAddress::Unknown => Err(NoCecDeviceTypeError {
+                address: CecLogicalAddress::Unknown,
+            }),
         }
     }
 }
@@ -1353,9 +1832,10 @@ mod cec_logical_address_tests {
                 | CecLogicalAddress::Unregistered
                 | CecLogicalAddress::Unknown => {
                     for result in [result_by_owned, result_by_ref] {
-                        assert!(result.is_err());
+                        let err = result.unwrap_err();
+                        assert_eq!(err, NoCecDeviceTypeError { address: *address });
                         assert_eq!(
-                            result.unwrap_err(),
+                            err.to_string(),
                             format!(
                                 "CecLogicalAddress::{:?} has no known CecDeviceType",
                                 address
@@ -1403,6 +1883,20 @@ impl CecDeviceTypeVec {
     }
 }
 
+impl From<cec_device_type_list> for CecDeviceTypeVec {
+    /// Convert device type list, skipping `Reserved` (unused) and unknown entries
+    fn from(device_types: cec_device_type_list) -> CecDeviceTypeVec {
+        CecDeviceTypeVec(
+            device_types
+                .types
+                .iter()
+                .filter_map(|type_id| CecDeviceType::from_repr(*type_id))
+                .filter(|type_id| *type_id != CecDeviceType::Reserved)
+                .collect(),
+        )
+    }
+}
+
 impl From<CecDeviceTypeVec> for cec_device_type_list {
     fn from(device_types: CecDeviceTypeVec) -> cec_device_type_list {
         let mut devices = cec_device_type_list {
@@ -1436,84 +1930,437 @@ mod cec_device_type_vec_tests {
         assert_eq!(ffi_devices.types[1], CecDeviceType::RecordingDevice.repr());
         assert_eq!(ffi_devices.types[2..], [CecDeviceType::Reserved.repr(); 3]);
     }
+
+    #[test]
+    fn test_from_ffi() {
+        let mut ffi_devices = cec_device_type_list {
+            types: [CecDeviceType::Reserved.repr(); 5],
+        };
+        ffi_devices.types[0] = CecDeviceType::PlaybackDevice.repr();
+        ffi_devices.types[1] = CecDeviceType::AudioSystem.repr();
+        let devices: CecDeviceTypeVec = ffi_devices.into();
+        assert_eq!(
+            devices.0.as_slice(),
+            [CecDeviceType::PlaybackDevice, CecDeviceType::AudioSystem]
+        );
+    }
 }
 
 struct CecCallbacks {
     pub key_press_callback: Option<Box<dyn FnMut(CecKeypress) + Send>>,
     pub command_received_callback: Option<Box<dyn FnMut(CecCommand) + Send>>,
     pub log_message_callbacks: Option<Box<dyn FnMut(CecLogMessage) + Send>>,
-    // TODO: implement missing callbacks (sourceActivated, commandHandler in libcec7, ...) below
+    pub source_activated_callback: Option<Box<FnSourceActivated>>,
+    pub alert_callback: Option<Box<FnAlert>>,
+    pub configuration_changed_callback: Option<Box<FnConfigurationChanged>>,
+    pub menu_state_changed_callback: Option<Box<FnMenuStateChanged>>,
+    #[cfg(abi7)]
+    pub command_handler_callback: Option<Box<FnCommandHandler>>,
+    pub event_callback: Option<Box<FnEvent>>,
+    command_watchers: CecCommandWatchers,
+    panic_policy: CecCallbackPanicPolicy,
+    #[doc = "time of opening, for the time of reported panics"]
+    opened: Instant,
 }
 
 pub type FnKeyPress = dyn FnMut(CecKeypress) + Send;
 pub type FnCommand = dyn FnMut(CecCommand) + Send;
 pub type FnLogMessage = dyn FnMut(CecLogMessage) + Send;
-pub type FnSourceActivated = dyn FnMut(CecLogicalAddress, bool);
+pub type FnSourceActivated = dyn FnMut(CecLogicalAddress, bool) + Send;
+pub type FnAlert = dyn FnMut(LibcecAlert, LibcecParameter) + Send;
+pub type FnConfigurationChanged = dyn FnMut(CecConfiguration) + Send;
+/// Menu state callback returns true to accept the change, false to refuse it
+pub type FnMenuStateChanged = dyn FnMut(CecMenuState) -> bool + Send;
+/// Command handler returns true when the command was handled, and libcec should not process it
+pub type FnCommandHandler = dyn FnMut(CecCommand) -> bool + Send;
+
+/// What to do when a callback panics
+///
+/// Callbacks are called from the libcec thread, the panic is always caught before it
+/// unwinds into libcec. It is logged, and reported to the log message callback as an
+/// error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CecCallbackPanicPolicy {
+    #[doc = "keep calling the callback"]
+    LogAndContinue,
+    #[doc = "stop calling the callback that panicked"]
+    DisableCallback,
+    #[doc = "abort the process"]
+    Abort,
+}
 
-extern "C" fn key_press_callback(rust_callbacks: *mut c_void, keypress_raw: *const cec_keypress) {
-    trace!("key_press_callback");
-    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
-    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
-        if let Some(keypress) = unsafe { keypress_raw.as_ref() } {
-            trace!("CecCallbacks: keypress.keycode {}", keypress.keycode);
-            if let Some(rust_callback) = &mut rust_callbacks.key_press_callback {
-                if let Ok(keypress) = (*keypress).try_into() {
-                    rust_callback(keypress);
-                }
-            }
-        }
+impl Default for CecCallbackPanicPolicy {
+    fn default() -> Self {
+        CecCallbackPanicPolicy::LogAndContinue
     }
 }
 
-extern "C" fn command_received_callback(
-    rust_callbacks: *mut c_void,
-    command_raw: *const cec_command,
-) {
-    trace!("command_received_callback");
-    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
-    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
-        if let Some(command) = unsafe { command_raw.as_ref() } {
-            trace!(
-                "command_received_callback: command.opcode {}",
-                command.opcode
-            );
-            if let Some(rust_callback) = &mut rust_callbacks.command_received_callback {
-                if let Ok(command) = (*command).try_into() {
-                    rust_callback(command);
-                }
-            }
-        }
+const LOG_MESSAGE_CALLBACK: &str = "log message";
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
     }
 }
 
-extern "C" fn log_message_callback(
-    rust_callbacks: *mut c_void,
-    log_message_raw: *const cec_log_message,
-) {
-    trace!("log_message_callback");
-    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
-    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
-        if let Some(log_message) = unsafe { log_message_raw.as_ref() } {
-            if let Some(rust_callback) = &mut rust_callbacks.log_message_callbacks {
-                if let Ok(log_message) = (*log_message).try_into() {
-                    rust_callback(log_message);
-                }
-            }
+impl CecCallbacks {
+    /// Consume the callbacks of `config`
+    fn take_from(config: &mut CecConnectionCfg, event_callback: Option<Box<FnEvent>>) -> Self {
+        CecCallbacks {
+            key_press_callback: config.key_press_callback.take(),
+            command_received_callback: config.command_received_callback.take(),
+            log_message_callbacks: config.log_message_callback.take(),
+            source_activated_callback: config.source_activated_callback.take(),
+            alert_callback: config.alert_callback.take(),
+            configuration_changed_callback: config.configuration_changed_callback.take(),
+            menu_state_changed_callback: config.menu_state_changed_callback.take(),
+            #[cfg(abi7)]
+            command_handler_callback: config.command_handler_callback.take(),
+            event_callback,
+            command_watchers: CecCommandWatchers::default(),
+            panic_policy: config.callback_panic_policy,
+            opened: Instant::now(),
         }
     }
-}
 
-static mut CALLBACKS: ICECCallbacks = ICECCallbacks {
-    logMessage: Option::Some(log_message_callback),
-    keyPress: Option::Some(key_press_callback),
-    commandReceived: Option::Some(command_received_callback),
-    configurationChanged: Option::None,
-    alert: Option::None,
-    menuStateChanged: Option::None,
-    sourceActivated: Option::None,
-    #[cfg(abi7)]
-    commandHandler: Option::None,
-};
+    /// Call the callback in `field`, if set, catching a panic according to the panic
+    /// policy. Returns `None` when the callback is not set or panicked.
+    fn call<C: ?Sized, R>(
+        &mut self,
+        name: &'static str,
+        field: fn(&mut CecCallbacks) -> &mut Option<Box<C>>,
+        call: impl FnOnce(&mut C) -> R,
+    ) -> Option<R> {
+        let callback = field(self).as_mut()?;
+        let payload = match panic::catch_unwind(AssertUnwindSafe(|| call(callback))) {
+            Ok(value) => return Some(value),
+            Err(payload) => payload,
+        };
+        let message = format!("{} callback panicked: {}", name, panic_message(&*payload));
+        error!("CecCallbacks: {}", message);
+        match self.panic_policy {
+            CecCallbackPanicPolicy::LogAndContinue => {}
+            CecCallbackPanicPolicy::DisableCallback => *field(self) = None,
+            CecCallbackPanicPolicy::Abort => process::abort(),
+        }
+        let log_message = CecLogMessage {
+            message,
+            level: CecLogLevel::Error,
+            time: self.opened.elapsed(),
+        };
+        if name == LOG_MESSAGE_CALLBACK {
+            // Do not feed the panic back to the callback that raised it
+            if let Some(event_callback) = &mut self.event_callback {
+                event_callback(CecEvent::LogMessage(log_message));
+            }
+        } else {
+            self.log_message(log_message);
+        }
+        None
+    }
+
+    fn key_press(&mut self, keypress: CecKeypress) {
+        self.call(
+            "key press",
+            |callbacks| &mut callbacks.key_press_callback,
+            |callback| callback(keypress),
+        );
+        if let Some(event_callback) = &mut self.event_callback {
+            event_callback(CecEvent::Keypress(keypress));
+        }
+    }
+
+    fn command_received(&mut self, command: CecCommand) {
+        self.command_watchers.notify(&command);
+        let received = command.clone();
+        self.call(
+            "command received",
+            |callbacks| &mut callbacks.command_received_callback,
+            |callback| callback(received),
+        );
+        if let Some(event_callback) = &mut self.event_callback {
+            event_callback(CecEvent::Command(command));
+        }
+    }
+
+    fn log_message(&mut self, log_message: CecLogMessage) {
+        let message = log_message.clone();
+        self.call(
+            LOG_MESSAGE_CALLBACK,
+            |callbacks| &mut callbacks.log_message_callbacks,
+            |callback| callback(message),
+        );
+        if let Some(event_callback) = &mut self.event_callback {
+            event_callback(CecEvent::LogMessage(log_message));
+        }
+    }
+
+    fn source_activated(&mut self, address: CecLogicalAddress, activated: bool) {
+        self.call(
+            "source activated",
+            |callbacks| &mut callbacks.source_activated_callback,
+            |callback| callback(address, activated),
+        );
+        if let Some(event_callback) = &mut self.event_callback {
+            event_callback(CecEvent::SourceActivated { address, activated });
+        }
+    }
+
+    fn alert(&mut self, alert: LibcecAlert, parameter: LibcecParameter) {
+        let alert_parameter = parameter.clone();
+        self.call(
+            "alert",
+            |callbacks| &mut callbacks.alert_callback,
+            |callback| callback(alert, alert_parameter),
+        );
+        if let Some(event_callback) = &mut self.event_callback {
+            event_callback(CecEvent::Alert(alert, parameter));
+        }
+    }
+
+    fn configuration_changed(&mut self, configuration: CecConfiguration) {
+        let changed = configuration.clone();
+        self.call(
+            "configuration changed",
+            |callbacks| &mut callbacks.configuration_changed_callback,
+            |callback| callback(changed),
+        );
+        if let Some(event_callback) = &mut self.event_callback {
+            event_callback(CecEvent::ConfigurationChanged(Box::new(configuration)));
+        }
+    }
+
+    /// Returns true when the menu state change was accepted
+    fn menu_state_changed(&mut self, state: CecMenuState) -> bool {
+        self.call(
+            "menu state changed",
+            |callbacks| &mut callbacks.menu_state_changed_callback,
+            |callback| callback(state),
+        )
+        .unwrap_or(false)
+    }
+
+    /// Returns true when the command was handled
+    #[cfg(abi7)]
+    fn handle_command(&mut self, command: CecCommand) -> bool {
+        self.call(
+            "command handler",
+            |callbacks| &mut callbacks.command_handler_callback,
+            |callback| callback(command),
+        )
+        .unwrap_or(false)
+    }
+
+    /// Dispatch `event` to the matching callback
+    fn event(&mut self, event: CecEvent) {
+        match event {
+            CecEvent::Keypress(keypress) => self.key_press(keypress),
+            CecEvent::Command(command) => self.command_received(command),
+            CecEvent::LogMessage(log_message) => self.log_message(log_message),
+            CecEvent::Alert(alert, parameter) => self.alert(alert, parameter),
+            CecEvent::SourceActivated { address, activated } => {
+                self.source_activated(address, activated)
+            }
+            CecEvent::ConfigurationChanged(configuration) => {
+                self.configuration_changed(*configuration)
+            }
+        }
+    }
+}
+
+extern "C" fn key_press_callback(rust_callbacks: *mut c_void, keypress_raw: *const cec_keypress) {
+    trace!("key_press_callback");
+    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
+    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
+        if let Some(keypress) = unsafe { keypress_raw.as_ref() } {
+            trace!("CecCallbacks: keypress.keycode {}", keypress.keycode);
+            rust_callbacks.key_press(CecKeypress::from(*keypress));
+        }
+    }
+}
+
+extern "C" fn command_received_callback(
+    rust_callbacks: *mut c_void,
+    command_raw: *const cec_command,
+) {
+    trace!("command_received_callback");
+    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
+    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
+        if let Some(command) = unsafe { command_raw.as_ref() } {
+            trace!(
+                "command_received_callback: command.opcode {}",
+                command.opcode
+            );
+            match CecCommand::try_from(*command) {
+                Ok(command) => rust_callbacks.command_received(command),
+                Err(err) => warn!("command_received_callback: dropping command: {}", err),
+            }
+        }
+    }
+}
+
+extern "C" fn log_message_callback(
+    rust_callbacks: *mut c_void,
+    log_message_raw: *const cec_log_message,
+) {
+    trace!("log_message_callback");
+    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
+    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
+        if let Some(log_message) = unsafe { log_message_raw.as_ref() } {
+            if let Ok(log_message) = CecLogMessage::try_from(*log_message) {
+                rust_callbacks.log_message(log_message);
+            }
+        }
+    }
+}
+
+extern "C" fn source_activated_callback(
+    rust_callbacks: *mut c_void,
+    logical_address: cec_logical_address,
+    activated: u8,
+) {
+    trace!("source_activated_callback");
+    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
+    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
+        match CecLogicalAddress::from_repr(logical_address) {
+            Some(address) => rust_callbacks.source_activated(address, activated != 0),
+            None => warn!(
+                "source_activated_callback: Could not convert logical address {}",
+                logical_address
+            ),
+        }
+    }
+}
+
+extern "C" fn alert_callback(
+    rust_callbacks: *mut c_void,
+    alert: libcec_alert,
+    parameter: libcec_parameter,
+) {
+    trace!("alert_callback");
+    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
+    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
+        match LibcecAlert::from_repr(alert) {
+            Some(alert) => rust_callbacks.alert(alert, LibcecParameter::from(parameter)),
+            None => warn!("alert_callback: Could not convert alert {}", alert),
+        }
+    }
+}
+
+extern "C" fn configuration_changed_callback(
+    rust_callbacks: *mut c_void,
+    configuration_raw: *const libcec_configuration,
+) {
+    trace!("configuration_changed_callback");
+    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
+    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
+        if let Some(configuration) = unsafe { configuration_raw.as_ref() } {
+            rust_callbacks.configuration_changed(configuration.into());
+        }
+    }
+}
+
+extern "C" fn menu_state_changed_callback(
+    rust_callbacks: *mut c_void,
+    state: cec_menu_state,
+) -> ::std::os::raw::c_int {
+    trace!("menu_state_changed_callback");
+    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
+    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
+        match CecMenuState::from_repr(state) {
+            Some(state) => return rust_callbacks.menu_state_changed(state).into(),
+            None => warn!(
+                "menu_state_changed_callback: Could not convert menu state {}",
+                state
+            ),
+        }
+    }
+    0
+}
+
+#[cfg(abi7)]
+extern "C" fn command_handler_callback(
+    rust_callbacks: *mut c_void,
+    command_raw: *const cec_command,
+) -> ::std::os::raw::c_int {
+    trace!("command_handler_callback");
+    let rust_callbacks: *mut CecCallbacks = rust_callbacks.cast();
+    if let Some(rust_callbacks) = unsafe { rust_callbacks.as_mut() } {
+        if let Some(command) = unsafe { command_raw.as_ref() } {
+            trace!(
+                "command_handler_callback: command.opcode {}",
+                command.opcode
+            );
+            if let Ok(command) = (*command).try_into() {
+                return rust_callbacks.handle_command(command).into();
+            }
+        }
+    }
+    0
+}
+
+/// libcec callback table, dispatching to the `CecCallbacks` registered as callback parameter
+///
+/// Every connection registers its own table, libcec keeps the pointer until destroyed.
+pub(crate) fn callback_table() -> ICECCallbacks {
+    ICECCallbacks {
+        logMessage: Option::Some(log_message_callback),
+        keyPress: Option::Some(key_press_callback),
+        commandReceived: Option::Some(command_received_callback),
+        configurationChanged: Option::Some(configuration_changed_callback),
+        alert: Option::Some(alert_callback),
+        menuStateChanged: Option::Some(menu_state_changed_callback),
+        sourceActivated: Option::Some(source_activated_callback),
+        #[cfg(abi7)]
+        commandHandler: Option::Some(command_handler_callback),
+    }
+}
+
+#[cfg(test)]
+mod callbacks_tests {
+    use super::*;
+
+    fn cfg() -> CecConnectionCfgBuilder {
+        CecConnectionCfgBuilder::default()
+            .device_name("test".into())
+            .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+    }
+
+    /// Call the menu state trampoline as libcec does
+    fn menu_state_changed(config: CecConnectionCfg, state: CecMenuState) -> ::std::os::raw::c_int {
+        let mut config = config;
+        let mut callbacks = CecCallbacks::take_from(&mut config, None);
+        let callbacks: *mut CecCallbacks = &mut callbacks;
+        menu_state_changed_callback(callbacks.cast(), state.repr())
+    }
+
+    #[test]
+    fn test_menu_state_changed() {
+        let accept_activated = || {
+            cfg()
+                .menu_state_changed_callback(Box::new(|state| state == CecMenuState::Activated))
+                .build()
+                .unwrap()
+        };
+        assert_eq!(
+            menu_state_changed(accept_activated(), CecMenuState::Activated),
+            1
+        );
+        assert_eq!(
+            menu_state_changed(accept_activated(), CecMenuState::Deactivated),
+            0
+        );
+        // Without a callback the change is refused
+        assert_eq!(
+            menu_state_changed(cfg().build().unwrap(), CecMenuState::Activated),
+            0
+        );
+    }
+}
 
 #[derive(Builder)]
 #[builder(pattern = "owned")]
@@ -1524,11 +2371,33 @@ pub struct CecConnectionCfg {
     pub command_received_callback: Option<Box<FnCommand>>,
     #[builder(default, setter(strip_option), pattern = "owned")]
     pub log_message_callback: Option<Box<FnLogMessage>>,
+    #[doc = "called when libcec becomes (true) or stops being (false) the active source"]
+    #[builder(default, setter(strip_option), pattern = "owned")]
+    pub source_activated_callback: Option<Box<FnSourceActivated>>,
+    #[doc = "called when libcec raises an alert, e.g. connection lost"]
+    #[builder(default, setter(strip_option), pattern = "owned")]
+    pub alert_callback: Option<Box<FnAlert>>,
+    #[doc = "called when libcec configuration changes"]
+    #[builder(default, setter(strip_option), pattern = "owned")]
+    pub configuration_changed_callback: Option<Box<FnConfigurationChanged>>,
+    #[doc = "called when the menu of this device is requested to be activated or deactivated. Return true to accept the change, false to refuse it"]
+    #[builder(default, setter(strip_option), pattern = "owned")]
+    pub menu_state_changed_callback: Option<Box<FnMenuStateChanged>>,
+    #[doc = "called for incoming commands before libcec handles them. Return true to mark the command handled, skipping libcec handling. Requires libcec 7 (abi7), open fails with CommandHandlerUnsupported otherwise"]
+    #[builder(default, setter(strip_option), pattern = "owned")]
+    pub command_handler_callback: Option<Box<FnCommandHandler>>,
+    #[doc = "what to do when a callback panics, see `CecCallbackPanicPolicy`"]
+    #[builder(default)]
+    pub callback_panic_policy: CecCallbackPanicPolicy,
 
     #[doc = "< the COM port to connect to. leave this untouched to autodetect"]
     #[builder(default, setter(strip_option))]
     pub port: Option<CString>,
 
+    #[doc = "adapter to open when `port` is not set and several adapters are detected. The first one is opened by default"]
+    #[builder(default, setter(strip_option), pattern = "owned")]
+    pub adapter: Option<CecAdapterSelector>,
+
     #[builder(default = "Duration::from_secs(5)")]
     pub open_timeout: Duration,
 
@@ -1543,7 +2412,7 @@ pub struct CecConnectionCfg {
     // optional cec_configuration items follow
     #[doc = "< the physical address of the CEC adapter"]
     #[builder(default, setter(strip_option))]
-    pub physical_address: Option<u16>,
+    pub physical_address: Option<CecPhysicalAddress>,
 
     #[doc = "< the logical address of the device to which the adapter is connected. only used when iPhysicalAddress = 0 or when the adapter doesn't support autodetection"]
     #[builder(default, setter(strip_option))]
@@ -1620,77 +2489,262 @@ pub struct CecConnectionCfg {
 
 pub type CecConnectionResult<T> = result::Result<T, CecConnectionResultError>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CecConnectionResultError {
     LibInitFailed,
     NoAdapterFound,
-    AdapterOpenFailed,
+    AdapterOpenFailed {
+        port: String,
+    },
     CallbackRegistrationFailed,
+    #[doc = "libcec failed to send the command, without telling why"]
     TransmitFailed,
+    #[doc = "destination did not acknowledge the command"]
+    NotAcknowledged {
+        destination: CecLogicalAddress,
+    },
+    #[doc = "destination did not reply in time"]
+    Timeout {
+        destination: CecLogicalAddress,
+    },
+    #[doc = "destination replied with `<Feature Abort>` to the command"]
+    FeatureAbort {
+        destination: CecLogicalAddress,
+        opcode: CecOpcode,
+        reason: CecAbortReason,
+    },
+    InvalidArgument(String),
+    CommandHandlerUnsupported,
+    ConfigurationFailed,
+}
+
+impl fmt::Display for CecConnectionResultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CecConnectionResultError::LibInitFailed => write!(f, "libcec initialisation failed"),
+            CecConnectionResultError::NoAdapterFound => write!(f, "no CEC adapter found"),
+            CecConnectionResultError::AdapterOpenFailed { port } => {
+                write!(f, "opening CEC adapter {} failed", port)
+            }
+            CecConnectionResultError::CallbackRegistrationFailed => {
+                write!(f, "registering libcec callbacks failed")
+            }
+            CecConnectionResultError::TransmitFailed => {
+                write!(f, "transmitting CEC command failed")
+            }
+            CecConnectionResultError::NotAcknowledged { destination } => {
+                write!(f, "{:?} did not acknowledge the command", destination)
+            }
+            CecConnectionResultError::Timeout { destination } => {
+                write!(f, "no reply from {:?}", destination)
+            }
+            CecConnectionResultError::FeatureAbort {
+                destination,
+                opcode,
+                reason,
+            } => write!(f, "{:?} aborted {:?}: {:?}", destination, opcode, reason),
+            CecConnectionResultError::InvalidArgument(reason) => {
+                write!(f, "invalid argument: {}", reason)
+            }
+            CecConnectionResultError::CommandHandlerUnsupported => {
+                write!(f, "command handler callback requires libcec 7")
+            }
+            CecConnectionResultError::ConfigurationFailed => {
+                write!(f, "reading or changing libcec configuration failed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CecConnectionResultError {}
+
+impl From<CecCommandBuildError> for CecConnectionResultError {
+    fn from(err: CecCommandBuildError) -> Self {
+        CecConnectionResultError::InvalidArgument(err.to_string())
+    }
 }
 
 pub struct CecConnection(
     pub CecConnectionCfg,
-    libcec_connection_t,
-    #[allow(dead_code)] Pin<Box<CecCallbacks>>,
+    Box<dyn CecBackend>,
+    CecCommandWatchers,
 );
 
+/// Answer of received `reply` to `request`, see `CecConnection::transmit_and_wait`
+///
+/// Returns `None` when `reply` is not from the destination of `request`, or not sent
+/// to the initiator of `request`.
+fn reply_to(
+    request: &CecCommand,
+    expected_opcode: CecOpcode,
+    reply: CecCommand,
+) -> Option<CecConnectionResult<CecCommand>> {
+    if request.destination != CecLogicalAddress::Unregistered
+        && reply.initiator != request.destination
+    {
+        return None;
+    }
+    // A reply to another device, e.g. aborting the same opcode for it
+    if reply.destination != request.initiator
+        && reply.destination != CecLogicalAddress::Unregistered
+    {
+        return None;
+    }
+    if reply.opcode == expected_opcode {
+        return Some(Ok(reply));
+    }
+    match reply.message() {
+        Ok(CecMessage::FeatureAbort { opcode, reason }) if opcode == request.opcode => {
+            Some(Err(CecConnectionResultError::FeatureAbort {
+                destination: reply.initiator,
+                opcode,
+                reason,
+            }))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod reply_tests {
+    use super::*;
+
+    fn from_tv(to: CecLogicalAddress, command: CecCommand) -> CecCommand {
+        CecCommand {
+            initiator: CecLogicalAddress::Tv,
+            destination: to,
+            ..command
+        }
+    }
+
+    #[test]
+    fn test_reply_to() {
+        let player = CecLogicalAddress::Playbackdevice1;
+        let request = CecCommand {
+            initiator: player,
+            ..CecCommand::give_device_power_status(CecLogicalAddress::Tv).unwrap()
+        };
+        let status = from_tv(
+            player,
+            CecCommand::report_power_status(player, CecPowerStatus::On).unwrap(),
+        );
+        assert_eq!(
+            reply_to(&request, CecOpcode::ReportPowerStatus, status.clone()),
+            Some(Ok(status.clone()))
+        );
+        let from_audio_system = CecCommand {
+            initiator: CecLogicalAddress::Audiosystem,
+            ..status
+        };
+        assert_eq!(
+            reply_to(&request, CecOpcode::ReportPowerStatus, from_audio_system),
+            None
+        );
+
+        let abort = CecCommand::feature_abort(
+            player,
+            CecOpcode::GiveDevicePowerStatus,
+            CecAbortReason::Refused,
+        )
+        .unwrap();
+        assert_eq!(
+            reply_to(
+                &request,
+                CecOpcode::ReportPowerStatus,
+                from_tv(player, abort.clone())
+            ),
+            Some(Err(CecConnectionResultError::FeatureAbort {
+                destination: CecLogicalAddress::Tv,
+                opcode: CecOpcode::GiveDevicePowerStatus,
+                reason: CecAbortReason::Refused,
+            }))
+        );
+        // The TV aborting the same request of another device
+        assert_eq!(
+            reply_to(
+                &request,
+                CecOpcode::ReportPowerStatus,
+                from_tv(CecLogicalAddress::Recordingdevice1, abort)
+            ),
+            None
+        );
+    }
+}
+
 impl CecConnection {
+    /// Transmit `command`
+    ///
+    /// An `Unknown` initiator, as left by the `CecCommand` constructors, is replaced with
+    /// the primary logical address of the connection. libcec rejects it otherwise.
     pub fn transmit(&self, command: CecCommand) -> CecConnectionResult<()> {
-        if unsafe { libcec_transmit(self.1, &command.into()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
+        self.1.transmit(self.with_initiator(command))
+    }
+
+    /// `command` with an `Unknown` initiator replaced by the primary logical address
+    fn with_initiator(&self, mut command: CecCommand) -> CecCommand {
+        if command.initiator == CecLogicalAddress::Unknown {
+            if let Ok(addresses) = self.1.get_logical_addresses() {
+                command.initiator = addresses.primary.into();
+            }
         }
+        command
     }
-    pub fn send_power_on_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
-        if unsafe { libcec_power_on_devices(self.1, address.repr()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
+
+    /// Transmit `command`, and wait up to `timeout` for the reply with `expected_opcode`
+    /// from its destination, e.g. `ReportPowerStatus` for `GiveDevicePowerStatus`
+    ///
+    /// The reply of a broadcast `command` may come from any device. Replies sent to other
+    /// devices are ignored.
+    ///
+    /// # Errors
+    ///
+    /// - errors of `transmit`
+    /// - FeatureAbort: the destination replied to `command` with `<Feature Abort>`
+    /// - Timeout: no reply was received within `timeout`
+    pub fn transmit_and_wait(
+        &self,
+        command: CecCommand,
+        expected_opcode: CecOpcode,
+        timeout: Duration,
+    ) -> CecConnectionResult<CecCommand> {
+        let deadline = Instant::now() + timeout;
+        let request = self.with_initiator(command);
+        let destination = request.destination;
+        // Watch before sending, the reply may arrive before the send returns
+        let replies = self.2.watch();
+        self.1.transmit(request.clone())?;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let reply = replies
+                .recv_timeout(remaining)
+                .map_err(|_| CecConnectionResultError::Timeout { destination })?;
+            if let Some(answer) = reply_to(&request, expected_opcode, reply) {
+                return answer;
+            }
         }
     }
+
+    pub fn send_power_on_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        self.1.send_power_on_devices(address)
+    }
     pub fn send_standby_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
-        if unsafe { libcec_standby_devices(self.1, address.repr()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.send_standby_devices(address)
     }
 
     pub fn set_active_source(&self, device_type: CecDeviceType) -> CecConnectionResult<()> {
-        if unsafe { libcec_set_active_source(self.1, device_type.repr()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.set_active_source(device_type)
     }
 
     pub fn get_active_source(&self) -> CecLogicalAddress {
-        let active_raw: cec_logical_address = unsafe { libcec_get_active_source(self.1) };
-        match CecLogicalAddress::from_repr(active_raw) {
-            Some(address) => address,
-            None => {
-                warn!("get_active_source: Could not convert logical address {} to rust enum. Returning Unknown", active_raw);
-                CecLogicalAddress::Unknown
-            }
-        }
+        self.1.get_active_source()
     }
 
     pub fn is_active_source(&self, address: CecLogicalAddress) -> bool {
-        (unsafe { libcec_is_active_source(self.1, address.repr()) }) != 0
+        self.1.is_active_source(address)
     }
 
     pub fn get_device_power_status(&self, address: CecLogicalAddress) -> CecPowerStatus {
-        let status_raw: cec_power_status =
-            unsafe { libcec_get_device_power_status(self.1, address.repr()) };
-        match CecPowerStatus::from_repr(status_raw) {
-            Some(status) => status,
-            None => {
-                warn!("get_device_power_status: Could not convert result {} to rust enum. Returning Unknown", status_raw);
-                CecPowerStatus::Unknown
-            }
-        }
+        self.1.get_device_power_status(address)
     }
 
     pub fn send_keypress(
@@ -1699,11 +2753,7 @@ impl CecConnection {
         key: CecUserControlCode,
         wait: bool,
     ) -> CecConnectionResult<()> {
-        if unsafe { libcec_send_keypress(self.1, address.repr(), key.repr(), wait.into()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.send_keypress(address, key, wait)
     }
 
     pub fn send_key_release(
@@ -1711,87 +2761,62 @@ impl CecConnection {
         address: CecLogicalAddress,
         wait: bool,
     ) -> CecConnectionResult<()> {
-        if unsafe { libcec_send_key_release(self.1, address.repr(), wait.into()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.send_key_release(address, wait)
     }
 
     pub fn volume_up(
         &self,
         send_release: bool,
     ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
-        match u8::try_from(unsafe { libcec_volume_up(self.1, send_release.into()) }) {
-            Ok(status) => KnownCecAudioStatus::try_from(status),
-            Err(_) => Err(TryFromCecAudioStatusError::Unknown),
-        }
+        self.1.volume_up(send_release)
     }
 
     pub fn volume_down(
         &self,
         send_release: bool,
     ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
-        match u8::try_from(unsafe { libcec_volume_down(self.1, send_release.into()) }) {
-            Ok(status) => KnownCecAudioStatus::try_from(status),
-            Err(_) => Err(TryFromCecAudioStatusError::Unknown),
-        }
+        self.1.volume_down(send_release)
     }
 
     pub fn mute_audio(
         &self,
         send_release: bool,
     ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
-        match u8::try_from(unsafe { libcec_mute_audio(self.1, send_release.into()) }) {
-            Ok(status) => KnownCecAudioStatus::try_from(status),
-            Err(_) => Err(TryFromCecAudioStatusError::Unknown),
-        }
+        self.1.mute_audio(send_release)
     }
 
     pub fn audio_toggle_mute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
-        KnownCecAudioStatus::try_from(unsafe { libcec_audio_toggle_mute(self.1) })
+        self.1.audio_toggle_mute()
     }
 
     pub fn audio_mute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
-        KnownCecAudioStatus::try_from(unsafe { libcec_audio_mute(self.1) })
+        self.1.audio_mute()
     }
 
     pub fn audio_unmute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
-        KnownCecAudioStatus::try_from(unsafe { libcec_audio_unmute(self.1) })
+        self.1.audio_unmute()
     }
 
     pub fn audio_get_status(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
-        KnownCecAudioStatus::try_from(unsafe { libcec_audio_get_status(self.1) })
+        self.1.audio_get_status()
     }
 
     pub fn set_inactive_view(&self) -> CecConnectionResult<()> {
-        if unsafe { libcec_set_inactive_view(self.1) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.set_inactive_view()
     }
 
     pub fn set_logical_address(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
-        if unsafe { libcec_set_logical_address(self.1, address.repr()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.set_logical_address(address)
     }
 
     pub fn get_logical_addresses(
         &self,
     ) -> Result<CecLogicalAddresses, TryFromCecLogicalAddressesError> {
-        CecLogicalAddresses::try_from(unsafe { libcec_get_logical_addresses(self.1) })
+        self.1.get_logical_addresses()
     }
 
     pub fn switch_monitoring(&self, enable: bool) -> CecConnectionResult<()> {
-        if unsafe { libcec_switch_monitoring(self.1, enable.into()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.switch_monitoring(enable)
     }
 
     pub fn set_deck_control_mode(
@@ -1799,52 +2824,127 @@ impl CecConnection {
         mode: CecDeckControlMode,
         send_update: bool,
     ) -> CecConnectionResult<()> {
-        if unsafe { libcec_set_deck_control_mode(self.1, mode.repr(), send_update.into()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.set_deck_control_mode(mode, send_update)
     }
 
     pub fn set_deck_info(&self, info: CecDeckInfo, send_update: bool) -> CecConnectionResult<()> {
-        if unsafe { libcec_set_deck_info(self.1, info.repr(), send_update.into()) } == 0 {
-            Err(CecConnectionResultError::TransmitFailed)
-        } else {
-            Ok(())
-        }
+        self.1.set_deck_info(info, send_update)
+    }
+
+    pub fn get_device_cec_version(&self, address: CecLogicalAddress) -> CecVersion {
+        self.1.get_device_cec_version(address)
+    }
+
+    pub fn get_device_menu_language(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<CecMenuLanguage> {
+        self.1.get_device_menu_language(address)
+    }
+
+    pub fn get_device_vendor_id(&self, address: CecLogicalAddress) -> CecVendorId {
+        self.1.get_device_vendor_id(address)
+    }
+
+    /// Physical address of the device
+    ///
+    /// Returns `None` when the physical address is not known
+    pub fn get_device_physical_address(
+        &self,
+        address: CecLogicalAddress,
+    ) -> Option<CecPhysicalAddress> {
+        self.1.get_device_physical_address(address)
+    }
+
+    pub fn get_device_osd_name(&self, address: CecLogicalAddress) -> CecConnectionResult<String> {
+        self.1.get_device_osd_name(address)
+    }
+
+    /// Poll the device, i.e. check whether it acknowledges messages sent to it
+    pub fn poll_device(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        self.1.poll_device(address)
+    }
+
+    /// Rescan the bus for devices, polling all logical addresses
+    pub fn rescan_devices(&self) {
+        self.1.rescan_devices()
+    }
+
+    /// Logical addresses of the devices present on the bus, including our own
+    pub fn get_active_devices(&self) -> Vec<CecLogicalAddress> {
+        self.1.get_active_devices()
+    }
+
+    /// Switch the TV to the device at logical address `address`
+    ///
+    /// Use `switch_to` to also wait for the device to become the active source.
+    pub fn set_stream_path_logical(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        self.1.set_stream_path_logical(address)
+    }
+
+    /// Switch the TV to the device at physical address `address`
+    pub fn set_stream_path_physical(&self, address: CecPhysicalAddress) -> CecConnectionResult<()> {
+        self.1.set_stream_path_physical(address)
+    }
+
+    /// Configuration currently used by libcec
+    ///
+    /// Unlike `self.0`, this includes changes made with `update_configuration`, and the
+    /// values detected by libcec such as the logical addresses.
+    pub fn current_configuration(&self) -> CecConnectionResult<CecConfiguration> {
+        self.1.current_configuration()
+    }
+
+    /// Change the configuration without reopening the connection
+    pub fn update_configuration(&self, update: &CecConfigurationUpdate) -> CecConnectionResult<()> {
+        self.1.update_configuration(update)
+    }
+
+    /// Whether the adapter can store its configuration, e.g. Pulse-Eight USB adapters
+    pub fn can_persist_configuration(&self) -> bool {
+        self.1.can_persist_configuration()
+    }
+
+    /// Apply `update` to the configuration, and store the result in the adapter
+    ///
+    /// The stored settings are used when the connection is opened with
+    /// `get_settings_from_rom`. Fails with ConfigurationFailed when the adapter cannot
+    /// store its configuration, see `can_persist_configuration`.
+    pub fn persist_configuration(
+        &self,
+        update: &CecConfigurationUpdate,
+    ) -> CecConnectionResult<()> {
+        self.1.persist_configuration(update)
+    }
+
+    /// Versions of the adapter and libcec, e.g. for bug reports
+    ///
+    /// See `CecAdapterInfo::for_port` to query an adapter without opening it.
+    pub fn adapter_info(&self) -> CecConnectionResult<CecAdapterInfo> {
+        self.1.adapter_info()
     }
 
     // Unimplemented:
     // extern DECLSPEC int libcec_set_physical_address(libcec_connection_t connection, uint16_t iPhysicalAddress);
     // extern DECLSPEC int libcec_set_menu_state(libcec_connection_t connection, CEC_NAMESPACE cec_menu_state state, int bSendUpdate);
     // extern DECLSPEC int libcec_set_osd_string(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address iLogicalAddress, CEC_NAMESPACE cec_display_control duration, const char* strMessage);
-    // extern DECLSPEC CEC_NAMESPACE cec_version libcec_get_device_cec_version(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address iLogicalAddress);
-    // extern DECLSPEC int libcec_get_device_menu_language(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address iLogicalAddress, CEC_NAMESPACE cec_menu_language language);
-    // extern DECLSPEC uint32_t libcec_get_device_vendor_id(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address iLogicalAddress);
-    // extern DECLSPEC uint16_t libcec_get_device_physical_address(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address iLogicalAddress);
-    // extern DECLSPEC int libcec_poll_device(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address iLogicalAddress);
-    // extern DECLSPEC CEC_NAMESPACE cec_logical_addresses libcec_get_active_devices(libcec_connection_t connection);
     // extern DECLSPEC int libcec_is_active_device(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address address);
     // extern DECLSPEC int libcec_is_active_device_type(libcec_connection_t connection, CEC_NAMESPACE cec_device_type type);
     // extern DECLSPEC int libcec_set_hdmi_port(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address baseDevice, uint8_t iPort);
-    // extern DECLSPEC int libcec_get_device_osd_name(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address iAddress, CEC_NAMESPACE cec_osd_name name);
-    // extern DECLSPEC int libcec_set_stream_path_logical(libcec_connection_t connection, CEC_NAMESPACE cec_logical_address iAddress);
-    // extern DECLSPEC int libcec_set_stream_path_physical(libcec_connection_t connection, uint16_t iPhysicalAddress);
-    // extern DECLSPEC int libcec_get_current_configuration(libcec_connection_t connection, CEC_NAMESPACE libcec_configuration* configuration);
-    // extern DECLSPEC int libcec_can_persist_configuration(libcec_connection_t connection);
-    // extern DECLSPEC int libcec_persist_configuration(libcec_connection_t connection, CEC_NAMESPACE libcec_configuration* configuration);
-    // extern DECLSPEC int libcec_set_configuration(libcec_connection_t connection, const CEC_NAMESPACE libcec_configuration* configuration);
-    // extern DECLSPEC void libcec_rescan_devices(libcec_connection_t connection);
     // extern DECLSPEC int libcec_is_libcec_active_source(libcec_connection_t connection);
-    // extern DECLSPEC int libcec_get_device_information(libcec_connection_t connection, const char* strPort, CEC_NAMESPACE libcec_configuration* config, uint32_t iTimeoutMs);
-    // extern DECLSPEC const char* libcec_get_lib_info(libcec_connection_t connection);
     // extern DECLSPEC void libcec_init_video_standalone(libcec_connection_t connection);
-    // extern DECLSPEC uint16_t libcec_get_adapter_vendor_id(libcec_connection_t connection);
-    // extern DECLSPEC uint16_t libcec_get_adapter_product_id(libcec_connection_t connection);
-    // extern DECLSPEC int8_t libcec_detect_adapters(libcec_connection_t connection, CEC_NAMESPACE cec_adapter_descriptor* deviceList, uint8_t iBufSize, const char* strDevicePath, int bQuickScan);
 }
 
 impl CecConnectionCfg {
+    /// Check that the callbacks are supported by the libcec version
+    fn check_callbacks(&self) -> CecConnectionResult<()> {
+        #[cfg(not(abi7))]
+        if self.command_handler_callback.is_some() {
+            return Err(CecConnectionResultError::CommandHandlerUnsupported);
+        }
+        Ok(())
+    }
+
     /// Open connection to configuration represented by this object
     ///
     ///
@@ -1854,96 +2954,44 @@ impl CecConnectionCfg {
     /// - LibInitFailed: libcec_sys::libcec_initialise fails
     /// - AdapterOpenFailed: libcec_sys::libcec_open fails
     /// - CallbackRegistrationFailed: libcec_sys::libcec_enable_callbacks fails
-    pub fn open(mut self) -> CecConnectionResult<CecConnection> {
-        let mut cfg: libcec_configuration = (&self).into();
-        // Consume self.*_callback and build CecCallbacks from those
-        let pinned_callbacks = Box::pin(CecCallbacks {
-            key_press_callback: self.key_press_callback.take(),
-            command_received_callback: self.command_received_callback.take(),
-            log_message_callbacks: self.log_message_callback.take(),
-        });
-        let rust_callbacks_as_void_ptr = &*pinned_callbacks as *const _ as *mut _;
-        let connection = CecConnection(
-            self,
-            unsafe { libcec_initialise(&mut cfg) },
-            pinned_callbacks,
-        );
-        if connection.1 as usize == 0 {
-            return Err(CecConnectionResultError::LibInitFailed);
-        }
-
-        let open_timeout = connection.0.open_timeout.as_millis() as u32;
-        match &connection.0.port {
-            Some(port) => {
-                if unsafe { libcec_open(connection.1, port.as_ptr(), open_timeout) } == 0 {
-                    return Err(CecConnectionResultError::AdapterOpenFailed);
-                }
-            }
-            None => {
-                let mut adapters = MaybeUninit::<[libcec_sys::cec_adapter_descriptor; 1]>::uninit();
-                let num_adapters = unsafe {
-                    libcec_sys::libcec_detect_adapters(
-                        connection.1,
-                        adapters.as_mut_ptr() as *mut _,
-                        1,
-                        std::ptr::null(),
-                        true as i32,
-                    )
-                };
-
-                if num_adapters == 0 {
-                    return Err(CecConnectionResultError::NoAdapterFound);
-                }
-
-                let port = unsafe { adapters.assume_init() }[0].strComName;
-                if unsafe { libcec_open(connection.1, port.as_ptr(), open_timeout) == 0 } {
-                    return Err(CecConnectionResultError::AdapterOpenFailed);
-                }
-            }
-        };
-
-        #[cfg(abi4)]
-        let callback_ret = unsafe {
-            libcec_sys::libcec_enable_callbacks(
-                connection.1,
-                rust_callbacks_as_void_ptr,
-                addr_of_mut!(CALLBACKS),
-            )
-        };
-        #[cfg(not(abi4))]
-        let callback_ret = unsafe {
-            libcec_sys::libcec_set_callbacks(
-                connection.1,
-                addr_of_mut!(CALLBACKS),
-                rust_callbacks_as_void_ptr,
-            )
-        };
-        if callback_ret == 0 {
-            return Err(CecConnectionResultError::CallbackRegistrationFailed);
-        }
-
-        Ok(connection)
+    /// - CommandHandlerUnsupported: command_handler_callback is set, but libcec is older than 7
+    pub fn open(self) -> CecConnectionResult<CecConnection> {
+        self.open_with_event_callback(None)
+    }
+
+    /// Open connection, additionally reporting all events to `event_callback`
+    pub(crate) fn open_with_event_callback(
+        mut self,
+        event_callback: Option<Box<FnEvent>>,
+    ) -> CecConnectionResult<CecConnection> {
+        self.check_callbacks()?;
+        let callbacks = CecCallbacks::take_from(&mut self, event_callback);
+        let command_watchers = callbacks.command_watchers.clone();
+        let backend = LibcecBackend::open(&self, callbacks)?;
+        Ok(CecConnection(self, Box::new(backend), command_watchers))
     }
 }
 
-impl Drop for CecConnection {
-    fn drop(&mut self) {
-        unsafe {
-            libcec_destroy(self.1);
-        }
+#[cfg(all(test, not(abi7)))]
+mod connection_cfg_tests {
+    use super::*;
+
+    #[test]
+    fn test_command_handler_unsupported() {
+        let result = CecConnectionCfgBuilder::default()
+            .device_name("test".into())
+            .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+            .command_handler_callback(Box::new(|_| true))
+            .build()
+            .unwrap()
+            .open();
+        assert!(matches!(
+            result,
+            Err(CecConnectionResultError::CommandHandlerUnsupported)
+        ));
     }
 }
 
-// libcec doesn't use thread local storage, so it's safe to send
-// CecConnection across threads.
-unsafe impl Send for CecConnection {}
-
-// libcec guards interior mutability behind mutex locks. It's hard to
-// verify that their implementation is perfectly thread safe, but they
-// wouldn't be using mutexes if they weren't aiming for thread safety,
-// so we trust that their implementation is correct.
-unsafe impl Sync for CecConnection {}
-
 impl From<&CecConnectionCfg> for libcec_configuration {
     fn from(config: &CecConnectionCfg) -> libcec_configuration {
         let mut cfg: libcec_configuration;
@@ -1955,7 +3003,7 @@ impl From<&CecConnectionCfg> for libcec_configuration {
         cfg.strDeviceName = first_n::<{ LIBCEC_OSD_NAME_SIZE as usize }>(&config.device_name);
         cfg.deviceTypes = config.device_types.clone().into();
         if let Some(v) = config.physical_address {
-            cfg.iPhysicalAddress = v;
+            cfg.iPhysicalAddress = v.into();
         }
         if let Some(v) = config.base_device {
             cfg.baseDevice = v.repr();
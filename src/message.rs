@@ -0,0 +1,1385 @@
+use crate::{
+    CecAbortReason, CecAnalogueBroadcastType, CecAudioRate, CecBroadcastSystem, CecCommand,
+    CecDatapacket, CecDeckControlMode, CecDeckInfo, CecDeviceType, CecDisplayControl,
+    CecExternalSourceSpecifier, CecLogicalAddress, CecMediaInfo, CecMenuLanguage,
+    CecMenuRequestType, CecMenuState, CecNotProgrammedErrorInfo, CecOpcode, CecPhysicalAddress,
+    CecPlayMode, CecPowerStatus, CecProgrammedIndicator, CecProgrammedInfo, CecRecordSourceType,
+    CecRecordStatusInfo, CecRecordingFlag, CecStatusRequest, CecSystemAudioStatus,
+    CecTimerClearedStatusData, CecTimerOverlapWarning, CecTunerDisplayInfo, CecUserControlCode,
+    CecVendorId, CecVersion, KnownCecAudioStatus,
+};
+
+use arrayvec::ArrayVec;
+use libcec_sys::{cec_opcode, cec_user_control_code, cec_vendor_id};
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+
+/// Transmit timeout used for commands created from messages, same as libcec default
+pub const DEFAULT_TRANSMIT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+pub(crate) const OSD_NAME_MAX_LENGTH: usize = 14;
+pub(crate) const OSD_STRING_MAX_LENGTH: usize = 13;
+pub(crate) const PROGRAM_TITLE_MAX_LENGTH: usize = 14;
+
+/// Analogue service, as used in e.g. `<Select Analogue Service>`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CecAnalogueService {
+    pub broadcast_type: CecAnalogueBroadcastType,
+    #[doc = "analogue frequency in steps of 62.5kHz"]
+    pub frequency: u16,
+    pub broadcast_system: CecBroadcastSystem,
+}
+
+/// Service identification of tuners and recording sources
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CecServiceId {
+    Analogue(CecAnalogueService),
+    #[doc = "raw [Digital Service Identification] operand"]
+    Digital([u8; 7]),
+}
+
+/// [Record Source] operand of `<Record On>`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CecRecordSource {
+    OwnSource,
+    DigitalService([u8; 7]),
+    AnalogueService(CecAnalogueService),
+    ExternalPlug(u8),
+    ExternalPhysicalAddress(CecPhysicalAddress),
+}
+
+/// External source of external timers
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CecExternalSource {
+    Plug(u8),
+    PhysicalAddress(CecPhysicalAddress),
+}
+
+/// Common timing operands of the timer messages
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CecTimerInfo {
+    pub day_of_month: u8,
+    pub month: u8,
+    pub start_hour: u8,
+    pub start_minute: u8,
+    pub duration_hours: u8,
+    pub duration_minutes: u8,
+    #[doc = "bitmask of CecRecordingSequence days, or CecRecordingSequence::OnceOnly"]
+    pub recording_sequence: u8,
+}
+
+/// Result of timer programming, part of `<Timer Status>`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CecTimerProgrammedStatus {
+    Programmed(CecProgrammedInfo),
+    NotProgrammed(CecNotProgrammedErrorInfo),
+}
+
+/// [Timer Status Data] operand of `<Timer Status>`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CecTimerStatus {
+    pub overlap_warning: CecTimerOverlapWarning,
+    pub media_info: CecMediaInfo,
+    pub programmed: CecTimerProgrammedStatus,
+    #[doc = "available duration as (hours, minutes), when reported"]
+    pub duration_available: Option<(u8, u8)>,
+}
+
+/// Typed representation of the opcode and parameters of a `CecCommand`
+///
+/// Addressing (initiator and destination) is not part of the message, see
+/// `CecMessage::to_command`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CecMessage {
+    #[doc = "message without opcode, used to poll for devices"]
+    Poll,
+    ActiveSource {
+        address: CecPhysicalAddress,
+    },
+    ImageViewOn,
+    TextViewOn,
+    InactiveSource {
+        address: CecPhysicalAddress,
+    },
+    RequestActiveSource,
+    RoutingChange {
+        original_address: CecPhysicalAddress,
+        new_address: CecPhysicalAddress,
+    },
+    RoutingInformation {
+        address: CecPhysicalAddress,
+    },
+    SetStreamPath {
+        address: CecPhysicalAddress,
+    },
+    Standby,
+    RecordOff,
+    RecordOn {
+        source: CecRecordSource,
+    },
+    RecordStatus {
+        status: CecRecordStatusInfo,
+    },
+    RecordTvScreen,
+    ClearAnalogueTimer {
+        timer: CecTimerInfo,
+        service: CecAnalogueService,
+    },
+    ClearDigitalTimer {
+        timer: CecTimerInfo,
+        service: [u8; 7],
+    },
+    ClearExternalTimer {
+        timer: CecTimerInfo,
+        source: CecExternalSource,
+    },
+    SetAnalogueTimer {
+        timer: CecTimerInfo,
+        service: CecAnalogueService,
+    },
+    SetDigitalTimer {
+        timer: CecTimerInfo,
+        service: [u8; 7],
+    },
+    SetExternalTimer {
+        timer: CecTimerInfo,
+        source: CecExternalSource,
+    },
+    SetTimerProgramTitle {
+        title: String,
+    },
+    TimerClearedStatus {
+        status: CecTimerClearedStatusData,
+    },
+    TimerStatus {
+        status: CecTimerStatus,
+    },
+    CecVersion {
+        version: CecVersion,
+    },
+    GetCecVersion,
+    GivePhysicalAddress,
+    GetMenuLanguage,
+    ReportPhysicalAddress {
+        address: CecPhysicalAddress,
+        device_type: CecDeviceType,
+    },
+    SetMenuLanguage {
+        language: CecMenuLanguage,
+    },
+    DeckControl {
+        mode: CecDeckControlMode,
+    },
+    DeckStatus {
+        info: CecDeckInfo,
+    },
+    GiveDeckStatus {
+        request: CecStatusRequest,
+    },
+    Play {
+        mode: CecPlayMode,
+    },
+    GiveTunerDeviceStatus {
+        request: CecStatusRequest,
+    },
+    SelectAnalogueService {
+        service: CecAnalogueService,
+    },
+    SelectDigitalService {
+        service: [u8; 7],
+    },
+    TunerDeviceStatus {
+        recording_flag: CecRecordingFlag,
+        display_info: CecTunerDisplayInfo,
+        service: Option<CecServiceId>,
+    },
+    TunerStepDecrement,
+    TunerStepIncrement,
+    DeviceVendorId {
+        vendor_id: CecVendorId,
+    },
+    GiveDeviceVendorId,
+    VendorCommand {
+        data: CecDatapacket,
+    },
+    VendorCommandWithId {
+        vendor_id: CecVendorId,
+        data: CecDatapacket,
+    },
+    VendorRemoteButtonDown {
+        rc_code: CecDatapacket,
+    },
+    VendorRemoteButtonUp,
+    SetOsdString {
+        display_control: CecDisplayControl,
+        text: String,
+    },
+    GiveOsdName,
+    SetOsdName {
+        name: String,
+    },
+    MenuRequest {
+        request: CecMenuRequestType,
+    },
+    MenuStatus {
+        state: CecMenuState,
+    },
+    UserControlPressed {
+        keycode: CecUserControlCode,
+    },
+    UserControlRelease,
+    GiveDevicePowerStatus,
+    ReportPowerStatus {
+        status: CecPowerStatus,
+    },
+    FeatureAbort {
+        opcode: CecOpcode,
+        reason: CecAbortReason,
+    },
+    Abort,
+    GiveAudioStatus,
+    GiveSystemAudioModeStatus,
+    ReportAudioStatus {
+        status: KnownCecAudioStatus,
+    },
+    SetSystemAudioMode {
+        status: CecSystemAudioStatus,
+    },
+    SystemAudioModeRequest {
+        #[doc = "physical address of the source to play, None to turn system audio off"]
+        address: Option<CecPhysicalAddress>,
+    },
+    SystemAudioModeStatus {
+        status: CecSystemAudioStatus,
+    },
+    SetAudioRate {
+        rate: CecAudioRate,
+    },
+    ReportShortAudioDescriptors {
+        descriptors: ArrayVec<[u8; 3], 4>,
+    },
+    RequestShortAudioDescriptors {
+        formats: ArrayVec<u8, 4>,
+    },
+    StartArc,
+    ReportArcStarted,
+    ReportArcEnded,
+    RequestArcStart,
+    RequestArcEnd,
+    EndArc,
+    Cdc {
+        initiator: CecPhysicalAddress,
+        data: CecDatapacket,
+    },
+}
+
+/// Error decoding `CecCommand` parameters into `CecMessage`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CecMessageError {
+    /// Parameters are too short for the opcode
+    MissingParameters { opcode: CecOpcode, length: usize },
+    /// Operand at given position of the parameters has invalid value
+    InvalidOperand {
+        opcode: CecOpcode,
+        position: usize,
+        value: u8,
+    },
+    /// Opcode is `CecOpcode::Other`, its parameters are not known
+    UnknownOpcode { opcode: CecOpcode },
+}
+
+impl fmt::Display for CecMessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CecMessageError::MissingParameters { opcode, length } => {
+                write!(f, "{:?} parameters too short: {} bytes", opcode, length)
+            }
+            CecMessageError::InvalidOperand {
+                opcode,
+                position,
+                value,
+            } => write!(
+                f,
+                "{:?} has invalid operand {:#04x} at {}",
+                opcode, value, position
+            ),
+            CecMessageError::UnknownOpcode { opcode } => {
+                write!(f, "unknown opcode {:#04x}", opcode.repr())
+            }
+        }
+    }
+}
+
+impl std::error::Error for CecMessageError {}
+
+struct ParameterReader<'a> {
+    opcode: CecOpcode,
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ParameterReader<'a> {
+    fn new(opcode: CecOpcode, data: &'a [u8]) -> Self {
+        Self {
+            opcode,
+            data,
+            position: 0,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    fn missing(&self) -> CecMessageError {
+        CecMessageError::MissingParameters {
+            opcode: self.opcode,
+            length: self.data.len(),
+        }
+    }
+
+    fn invalid(&self, position: usize) -> CecMessageError {
+        CecMessageError::InvalidOperand {
+            opcode: self.opcode,
+            position,
+            value: self.data[position],
+        }
+    }
+
+    fn u8(&mut self) -> Result<u8, CecMessageError> {
+        let value = *self.data.get(self.position).ok_or_else(|| self.missing())?;
+        self.position += 1;
+        Ok(value)
+    }
+
+    fn u16(&mut self) -> Result<u16, CecMessageError> {
+        Ok(u16::from(self.u8()?) << 8 | u16::from(self.u8()?))
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], CecMessageError> {
+        if self.remaining() < N {
+            return Err(self.missing());
+        }
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(&self.data[self.position..self.position + N]);
+        self.position += N;
+        Ok(bytes)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.position..];
+        self.position = self.data.len();
+        rest
+    }
+
+    /// Read one byte operand and convert it with `convert`
+    fn operand<T>(&mut self, convert: impl Fn(u8) -> Option<T>) -> Result<T, CecMessageError> {
+        let position = self.position;
+        convert(self.u8()?).ok_or_else(|| self.invalid(position))
+    }
+
+    fn bcd(&mut self, max: u8) -> Result<u8, CecMessageError> {
+        self.operand(|value| {
+            let (tens, ones) = (value >> 4, value & 0xF);
+            if tens > 9 || ones > 9 || tens * 10 + ones > max {
+                None
+            } else {
+                Some(tens * 10 + ones)
+            }
+        })
+    }
+
+    fn physical_address(&mut self) -> Result<CecPhysicalAddress, CecMessageError> {
+        let position = self.position;
+        CecPhysicalAddress::new(self.u16()?).ok_or_else(|| self.invalid(position))
+    }
+
+    fn vendor_id(&mut self) -> Result<CecVendorId, CecMessageError> {
+        let raw = self.bytes::<3>()?;
+        Ok(CecVendorId::from(
+            (u32::from(raw[0]) << 16 | u32::from(raw[1]) << 8 | u32::from(raw[2])) as cec_vendor_id,
+        ))
+    }
+
+    /// Read remaining parameters as string, at least one character is required
+    fn string(&mut self) -> Result<String, CecMessageError> {
+        if self.remaining() == 0 {
+            return Err(self.missing());
+        }
+        Ok(String::from_utf8_lossy(self.rest()).into_owned())
+    }
+
+    fn datapacket(&mut self) -> CecDatapacket {
+        let mut packet = CecDatapacket(ArrayVec::new());
+        // Cannot fail, reader data comes from CecDatapacket
+        packet.0.try_extend_from_slice(self.rest()).unwrap();
+        packet
+    }
+
+    fn analogue_service(&mut self) -> Result<CecAnalogueService, CecMessageError> {
+        Ok(CecAnalogueService {
+            broadcast_type: self
+                .operand(|value| CecAnalogueBroadcastType::from_repr(value.into()))?,
+            frequency: self.u16()?,
+            broadcast_system: self.operand(|value| CecBroadcastSystem::from_repr(value.into()))?,
+        })
+    }
+
+    fn timer_info(&mut self) -> Result<CecTimerInfo, CecMessageError> {
+        Ok(CecTimerInfo {
+            day_of_month: self.operand(|value| Some(value).filter(|v| (1..=31).contains(v)))?,
+            month: self.operand(|value| Some(value).filter(|v| (1..=12).contains(v)))?,
+            start_hour: self.bcd(23)?,
+            start_minute: self.bcd(59)?,
+            duration_hours: self.bcd(99)?,
+            duration_minutes: self.bcd(59)?,
+            recording_sequence: self.u8()?,
+        })
+    }
+
+    fn external_source(&mut self) -> Result<CecExternalSource, CecMessageError> {
+        match self.operand(|value| CecExternalSourceSpecifier::from_repr(value.into()))? {
+            CecExternalSourceSpecifier::Plug => Ok(CecExternalSource::Plug(self.u8()?)),
+            CecExternalSourceSpecifier::PhysicalAddress => {
+                Ok(CecExternalSource::PhysicalAddress(self.physical_address()?))
+            }
+        }
+    }
+
+    fn record_source(&mut self) -> Result<CecRecordSource, CecMessageError> {
+        match self.operand(|value| CecRecordSourceType::from_repr(value.into()))? {
+            CecRecordSourceType::OwnSource => Ok(CecRecordSource::OwnSource),
+            CecRecordSourceType::DigitalService => {
+                Ok(CecRecordSource::DigitalService(self.bytes()?))
+            }
+            CecRecordSourceType::AnalogueService => {
+                Ok(CecRecordSource::AnalogueService(self.analogue_service()?))
+            }
+            CecRecordSourceType::ExternalPlus => Ok(CecRecordSource::ExternalPlug(self.u8()?)),
+            CecRecordSourceType::ExternalPhysicalAddress => Ok(
+                CecRecordSource::ExternalPhysicalAddress(self.physical_address()?),
+            ),
+        }
+    }
+
+    fn timer_status(&mut self) -> Result<CecTimerStatus, CecMessageError> {
+        let position = self.position;
+        let value = self.u8()?;
+        let overlap_warning = CecTimerOverlapWarning::from_repr((value >> 7).into());
+        let media_info = CecMediaInfo::from_repr(((value >> 5) & 0x3).into());
+        let programmed = match CecProgrammedIndicator::from_repr(((value >> 4) & 0x1).into()) {
+            Some(CecProgrammedIndicator::Programmed) => {
+                CecProgrammedInfo::from_repr((value & 0xF).into())
+                    .map(CecTimerProgrammedStatus::Programmed)
+            }
+            Some(CecProgrammedIndicator::NotProgrammed) => {
+                CecNotProgrammedErrorInfo::from_repr((value & 0xF).into())
+                    .map(CecTimerProgrammedStatus::NotProgrammed)
+            }
+            None => None,
+        };
+        match (overlap_warning, media_info, programmed) {
+            (Some(overlap_warning), Some(media_info), Some(programmed)) => Ok(CecTimerStatus {
+                overlap_warning,
+                media_info,
+                programmed,
+                duration_available: if self.remaining() >= 2 {
+                    Some((self.bcd(99)?, self.bcd(59)?))
+                } else {
+                    None
+                },
+            }),
+            _ => Err(self.invalid(position)),
+        }
+    }
+
+    fn tuner_device_status(&mut self) -> Result<CecMessage, CecMessageError> {
+        let position = self.position;
+        let value = self.u8()?;
+        let recording_flag = CecRecordingFlag::from_repr((value >> 7).into())
+            .ok_or_else(|| self.invalid(position))?;
+        let display_info = CecTunerDisplayInfo::from_repr((value & 0x7F).into())
+            .ok_or_else(|| self.invalid(position))?;
+        let service = match self.remaining() {
+            0 => None,
+            n if n >= 7 => Some(CecServiceId::Digital(self.bytes()?)),
+            _ => Some(CecServiceId::Analogue(self.analogue_service()?)),
+        };
+        Ok(CecMessage::TunerDeviceStatus {
+            recording_flag,
+            display_info,
+            service,
+        })
+    }
+}
+
+fn push_u16(parameters: &mut ArrayVec<u8, 64>, value: u16) {
+    parameters.push((value >> 8) as u8);
+    parameters.push(value as u8);
+}
+
+fn push_physical_address(parameters: &mut ArrayVec<u8, 64>, address: CecPhysicalAddress) {
+    push_u16(parameters, address.into());
+}
+
+fn push_bcd(parameters: &mut ArrayVec<u8, 64>, value: u8) {
+    parameters.push((value / 10) << 4 | (value % 10));
+}
+
+fn push_str(parameters: &mut ArrayVec<u8, 64>, value: &str, max_length: usize) {
+    parameters.extend(value.bytes().take(max_length));
+}
+
+fn push_datapacket(parameters: &mut ArrayVec<u8, 64>, packet: &CecDatapacket) {
+    let free = parameters.remaining_capacity();
+    parameters.extend(packet.0.iter().copied().take(free));
+}
+
+fn push_vendor_id(parameters: &mut ArrayVec<u8, 64>, vendor_id: CecVendorId) {
+    let raw = vendor_id.repr();
+    parameters.push((raw >> 16) as u8);
+    parameters.push((raw >> 8) as u8);
+    parameters.push(raw as u8);
+}
+
+fn push_analogue_service(parameters: &mut ArrayVec<u8, 64>, service: &CecAnalogueService) {
+    parameters.push(service.broadcast_type.repr() as u8);
+    push_u16(parameters, service.frequency);
+    parameters.push(service.broadcast_system.repr() as u8);
+}
+
+fn push_timer_info(parameters: &mut ArrayVec<u8, 64>, timer: &CecTimerInfo) {
+    parameters.push(timer.day_of_month);
+    parameters.push(timer.month);
+    push_bcd(parameters, timer.start_hour);
+    push_bcd(parameters, timer.start_minute);
+    push_bcd(parameters, timer.duration_hours);
+    push_bcd(parameters, timer.duration_minutes);
+    parameters.push(timer.recording_sequence);
+}
+
+fn push_external_source(parameters: &mut ArrayVec<u8, 64>, source: &CecExternalSource) {
+    match source {
+        CecExternalSource::Plug(plug) => {
+            parameters.push(CecExternalSourceSpecifier::Plug.repr() as u8);
+            parameters.push(*plug);
+        }
+        CecExternalSource::PhysicalAddress(address) => {
+            parameters.push(CecExternalSourceSpecifier::PhysicalAddress.repr() as u8);
+            push_physical_address(parameters, *address);
+        }
+    }
+}
+
+fn push_record_source(parameters: &mut ArrayVec<u8, 64>, source: &CecRecordSource) {
+    match source {
+        CecRecordSource::OwnSource => {
+            parameters.push(CecRecordSourceType::OwnSource.repr() as u8);
+        }
+        CecRecordSource::DigitalService(service) => {
+            parameters.push(CecRecordSourceType::DigitalService.repr() as u8);
+            parameters.extend(service.iter().copied());
+        }
+        CecRecordSource::AnalogueService(service) => {
+            parameters.push(CecRecordSourceType::AnalogueService.repr() as u8);
+            push_analogue_service(parameters, service);
+        }
+        CecRecordSource::ExternalPlug(plug) => {
+            parameters.push(CecRecordSourceType::ExternalPlus.repr() as u8);
+            parameters.push(*plug);
+        }
+        CecRecordSource::ExternalPhysicalAddress(address) => {
+            parameters.push(CecRecordSourceType::ExternalPhysicalAddress.repr() as u8);
+            push_physical_address(parameters, *address);
+        }
+    }
+}
+
+fn push_timer_status(parameters: &mut ArrayVec<u8, 64>, status: &CecTimerStatus) {
+    let (indicator, info) = match status.programmed {
+        CecTimerProgrammedStatus::Programmed(info) => {
+            (CecProgrammedIndicator::Programmed, info.repr() as u8)
+        }
+        CecTimerProgrammedStatus::NotProgrammed(info) => {
+            (CecProgrammedIndicator::NotProgrammed, info.repr() as u8)
+        }
+    };
+    parameters.push(
+        (status.overlap_warning.repr() as u8) << 7
+            | (status.media_info.repr() as u8) << 5
+            | (indicator.repr() as u8) << 4
+            | info,
+    );
+    if let Some((hours, minutes)) = status.duration_available {
+        push_bcd(parameters, hours);
+        push_bcd(parameters, minutes);
+    }
+}
+
+impl CecMessage {
+    /// Decode message from opcode and raw parameters
+    ///
+    /// Additional parameters beyond the operands of the opcode are ignored, as
+    /// required by the CEC specification.
+    ///
+    /// # Errors
+    ///
+    /// Error is returned in following cases
+    /// - MissingParameters: parameters are too short for the opcode
+    /// - InvalidOperand: operand value is not valid
+    /// - UnknownOpcode: opcode is `CecOpcode::Other`
+    pub fn decode(opcode: CecOpcode, parameters: &[u8]) -> Result<CecMessage, CecMessageError> {
+        let mut reader = ParameterReader::new(opcode, parameters);
+        let r = &mut reader;
+        Ok(match opcode {
+            CecOpcode::None => CecMessage::Poll,
+            CecOpcode::ActiveSource => CecMessage::ActiveSource {
+                address: r.physical_address()?,
+            },
+            CecOpcode::ImageViewOn => CecMessage::ImageViewOn,
+            CecOpcode::TextViewOn => CecMessage::TextViewOn,
+            CecOpcode::InactiveSource => CecMessage::InactiveSource {
+                address: r.physical_address()?,
+            },
+            CecOpcode::RequestActiveSource => CecMessage::RequestActiveSource,
+            CecOpcode::RoutingChange => CecMessage::RoutingChange {
+                original_address: r.physical_address()?,
+                new_address: r.physical_address()?,
+            },
+            CecOpcode::RoutingInformation => CecMessage::RoutingInformation {
+                address: r.physical_address()?,
+            },
+            CecOpcode::SetStreamPath => CecMessage::SetStreamPath {
+                address: r.physical_address()?,
+            },
+            CecOpcode::Standby => CecMessage::Standby,
+            CecOpcode::RecordOff => CecMessage::RecordOff,
+            CecOpcode::RecordOn => CecMessage::RecordOn {
+                source: r.record_source()?,
+            },
+            CecOpcode::RecordStatus => CecMessage::RecordStatus {
+                status: r.operand(|value| CecRecordStatusInfo::from_repr(value.into()))?,
+            },
+            CecOpcode::RecordTvScreen => CecMessage::RecordTvScreen,
+            CecOpcode::ClearAnalogueTimer => CecMessage::ClearAnalogueTimer {
+                timer: r.timer_info()?,
+                service: r.analogue_service()?,
+            },
+            CecOpcode::ClearDigitalTimer => CecMessage::ClearDigitalTimer {
+                timer: r.timer_info()?,
+                service: r.bytes()?,
+            },
+            CecOpcode::ClearExternalTimer => CecMessage::ClearExternalTimer {
+                timer: r.timer_info()?,
+                source: r.external_source()?,
+            },
+            CecOpcode::SetAnalogueTimer => CecMessage::SetAnalogueTimer {
+                timer: r.timer_info()?,
+                service: r.analogue_service()?,
+            },
+            CecOpcode::SetDigitalTimer => CecMessage::SetDigitalTimer {
+                timer: r.timer_info()?,
+                service: r.bytes()?,
+            },
+            CecOpcode::SetExternalTimer => CecMessage::SetExternalTimer {
+                timer: r.timer_info()?,
+                source: r.external_source()?,
+            },
+            CecOpcode::SetTimerProgramTitle => {
+                CecMessage::SetTimerProgramTitle { title: r.string()? }
+            }
+            CecOpcode::TimerClearedStatus => CecMessage::TimerClearedStatus {
+                status: r.operand(|value| CecTimerClearedStatusData::from_repr(value.into()))?,
+            },
+            CecOpcode::TimerStatus => CecMessage::TimerStatus {
+                status: r.timer_status()?,
+            },
+            CecOpcode::CecVersion => CecMessage::CecVersion {
+                version: r.operand(|value| CecVersion::from_repr(value.into()))?,
+            },
+            CecOpcode::GetCecVersion => CecMessage::GetCecVersion,
+            CecOpcode::GivePhysicalAddress => CecMessage::GivePhysicalAddress,
+            CecOpcode::GetMenuLanguage => CecMessage::GetMenuLanguage,
+            CecOpcode::ReportPhysicalAddress => CecMessage::ReportPhysicalAddress {
+                address: r.physical_address()?,
+                device_type: r.operand(|value| CecDeviceType::from_repr(value.into()))?,
+            },
+            CecOpcode::SetMenuLanguage => {
+                let position = r.position;
+                let language = r.bytes::<3>()?;
+                CecMessage::SetMenuLanguage {
+                    language: std::str::from_utf8(&language)
+                        .ok()
+                        .and_then(CecMenuLanguage::new)
+                        .ok_or_else(|| r.invalid(position))?,
+                }
+            }
+            CecOpcode::DeckControl => CecMessage::DeckControl {
+                mode: r.operand(|value| CecDeckControlMode::from_repr(value.into()))?,
+            },
+            CecOpcode::DeckStatus => CecMessage::DeckStatus {
+                info: r.operand(|value| CecDeckInfo::from_repr(value.into()))?,
+            },
+            CecOpcode::GiveDeckStatus => CecMessage::GiveDeckStatus {
+                request: r.operand(|value| CecStatusRequest::from_repr(value.into()))?,
+            },
+            CecOpcode::Play => CecMessage::Play {
+                mode: r.operand(|value| CecPlayMode::from_repr(value.into()))?,
+            },
+            CecOpcode::GiveTunerDeviceStatus => CecMessage::GiveTunerDeviceStatus {
+                request: r.operand(|value| CecStatusRequest::from_repr(value.into()))?,
+            },
+            CecOpcode::SelectAnalogueService => CecMessage::SelectAnalogueService {
+                service: r.analogue_service()?,
+            },
+            CecOpcode::SelectDigitalService => CecMessage::SelectDigitalService {
+                service: r.bytes()?,
+            },
+            CecOpcode::TunerDeviceStatus => r.tuner_device_status()?,
+            CecOpcode::TunerStepDecrement => CecMessage::TunerStepDecrement,
+            CecOpcode::TunerStepIncrement => CecMessage::TunerStepIncrement,
+            CecOpcode::DeviceVendorId => CecMessage::DeviceVendorId {
+                vendor_id: r.vendor_id()?,
+            },
+            CecOpcode::GiveDeviceVendorId => CecMessage::GiveDeviceVendorId,
+            CecOpcode::VendorCommand => CecMessage::VendorCommand {
+                data: r.datapacket(),
+            },
+            CecOpcode::VendorCommandWithId => CecMessage::VendorCommandWithId {
+                vendor_id: r.vendor_id()?,
+                data: r.datapacket(),
+            },
+            CecOpcode::VendorRemoteButtonDown => CecMessage::VendorRemoteButtonDown {
+                rc_code: r.datapacket(),
+            },
+            CecOpcode::VendorRemoteButtonUp => CecMessage::VendorRemoteButtonUp,
+            CecOpcode::SetOsdString => CecMessage::SetOsdString {
+                display_control: r.operand(|value| CecDisplayControl::from_repr(value.into()))?,
+                text: r.string()?,
+            },
+            CecOpcode::GiveOsdName => CecMessage::GiveOsdName,
+            CecOpcode::SetOsdName => CecMessage::SetOsdName { name: r.string()? },
+            CecOpcode::MenuRequest => CecMessage::MenuRequest {
+                request: r.operand(|value| CecMenuRequestType::from_repr(value.into()))?,
+            },
+            CecOpcode::MenuStatus => CecMessage::MenuStatus {
+                state: r.operand(|value| CecMenuState::from_repr(value.into()))?,
+            },
+            CecOpcode::UserControlPressed => CecMessage::UserControlPressed {
+                keycode: CecUserControlCode::from(cec_user_control_code::from(r.u8()?)),
+            },
+            CecOpcode::UserControlRelease => CecMessage::UserControlRelease,
+            CecOpcode::GiveDevicePowerStatus => CecMessage::GiveDevicePowerStatus,
+            CecOpcode::ReportPowerStatus => CecMessage::ReportPowerStatus {
+                status: r.operand(|value| CecPowerStatus::from_repr(value.into()))?,
+            },
+            CecOpcode::FeatureAbort => CecMessage::FeatureAbort {
+                opcode: CecOpcode::from(cec_opcode::from(r.u8()?)),
+                reason: r.operand(|value| CecAbortReason::from_repr(value.into()))?,
+            },
+            CecOpcode::Abort => CecMessage::Abort,
+            CecOpcode::GiveAudioStatus => CecMessage::GiveAudioStatus,
+            CecOpcode::GiveSystemAudioModeStatus => CecMessage::GiveSystemAudioModeStatus,
+            CecOpcode::ReportAudioStatus => CecMessage::ReportAudioStatus {
+                status: r.operand(|value| KnownCecAudioStatus::try_from(value).ok())?,
+            },
+            CecOpcode::SetSystemAudioMode => CecMessage::SetSystemAudioMode {
+                status: r.operand(|value| CecSystemAudioStatus::from_repr(value.into()))?,
+            },
+            CecOpcode::SystemAudioModeRequest => CecMessage::SystemAudioModeRequest {
+                address: if r.remaining() > 0 {
+                    Some(r.physical_address()?)
+                } else {
+                    None
+                },
+            },
+            CecOpcode::SystemAudioModeStatus => CecMessage::SystemAudioModeStatus {
+                status: r.operand(|value| CecSystemAudioStatus::from_repr(value.into()))?,
+            },
+            CecOpcode::SetAudioRate => CecMessage::SetAudioRate {
+                rate: r.operand(|value| CecAudioRate::from_repr(value.into()))?,
+            },
+            CecOpcode::ReportShortAudioDescriptors => {
+                let mut descriptors = ArrayVec::new();
+                while r.remaining() >= 3 && !descriptors.is_full() {
+                    descriptors.push(r.bytes()?);
+                }
+                if descriptors.is_empty() {
+                    return Err(r.missing());
+                }
+                CecMessage::ReportShortAudioDescriptors { descriptors }
+            }
+            CecOpcode::RequestShortAudioDescriptors => {
+                let mut formats = ArrayVec::new();
+                while r.remaining() > 0 && !formats.is_full() {
+                    formats.push(r.u8()?);
+                }
+                if formats.is_empty() {
+                    return Err(r.missing());
+                }
+                CecMessage::RequestShortAudioDescriptors { formats }
+            }
+            CecOpcode::StartArc => CecMessage::StartArc,
+            CecOpcode::ReportArcStarted => CecMessage::ReportArcStarted,
+            CecOpcode::ReportArcEnded => CecMessage::ReportArcEnded,
+            CecOpcode::RequestArcStart => CecMessage::RequestArcStart,
+            CecOpcode::RequestArcEnd => CecMessage::RequestArcEnd,
+            CecOpcode::EndArc => CecMessage::EndArc,
+            CecOpcode::Cdc => CecMessage::Cdc {
+                initiator: r.physical_address()?,
+                data: r.datapacket(),
+            },
+            CecOpcode::Other(_) => return Err(CecMessageError::UnknownOpcode { opcode }),
+        })
+    }
+
+    /// Opcode of the message. `CecOpcode::None` for `Poll`
+    pub fn opcode(&self) -> CecOpcode {
+        match self {
+            CecMessage::Poll => CecOpcode::None,
+            CecMessage::ActiveSource { .. } => CecOpcode::ActiveSource,
+            CecMessage::ImageViewOn => CecOpcode::ImageViewOn,
+            CecMessage::TextViewOn => CecOpcode::TextViewOn,
+            CecMessage::InactiveSource { .. } => CecOpcode::InactiveSource,
+            CecMessage::RequestActiveSource => CecOpcode::RequestActiveSource,
+            CecMessage::RoutingChange { .. } => CecOpcode::RoutingChange,
+            CecMessage::RoutingInformation { .. } => CecOpcode::RoutingInformation,
+            CecMessage::SetStreamPath { .. } => CecOpcode::SetStreamPath,
+            CecMessage::Standby => CecOpcode::Standby,
+            CecMessage::RecordOff => CecOpcode::RecordOff,
+            CecMessage::RecordOn { .. } => CecOpcode::RecordOn,
+            CecMessage::RecordStatus { .. } => CecOpcode::RecordStatus,
+            CecMessage::RecordTvScreen => CecOpcode::RecordTvScreen,
+            CecMessage::ClearAnalogueTimer { .. } => CecOpcode::ClearAnalogueTimer,
+            CecMessage::ClearDigitalTimer { .. } => CecOpcode::ClearDigitalTimer,
+            CecMessage::ClearExternalTimer { .. } => CecOpcode::ClearExternalTimer,
+            CecMessage::SetAnalogueTimer { .. } => CecOpcode::SetAnalogueTimer,
+            CecMessage::SetDigitalTimer { .. } => CecOpcode::SetDigitalTimer,
+            CecMessage::SetExternalTimer { .. } => CecOpcode::SetExternalTimer,
+            CecMessage::SetTimerProgramTitle { .. } => CecOpcode::SetTimerProgramTitle,
+            CecMessage::TimerClearedStatus { .. } => CecOpcode::TimerClearedStatus,
+            CecMessage::TimerStatus { .. } => CecOpcode::TimerStatus,
+            CecMessage::CecVersion { .. } => CecOpcode::CecVersion,
+            CecMessage::GetCecVersion => CecOpcode::GetCecVersion,
+            CecMessage::GivePhysicalAddress => CecOpcode::GivePhysicalAddress,
+            CecMessage::GetMenuLanguage => CecOpcode::GetMenuLanguage,
+            CecMessage::ReportPhysicalAddress { .. } => CecOpcode::ReportPhysicalAddress,
+            CecMessage::SetMenuLanguage { .. } => CecOpcode::SetMenuLanguage,
+            CecMessage::DeckControl { .. } => CecOpcode::DeckControl,
+            CecMessage::DeckStatus { .. } => CecOpcode::DeckStatus,
+            CecMessage::GiveDeckStatus { .. } => CecOpcode::GiveDeckStatus,
+            CecMessage::Play { .. } => CecOpcode::Play,
+            CecMessage::GiveTunerDeviceStatus { .. } => CecOpcode::GiveTunerDeviceStatus,
+            CecMessage::SelectAnalogueService { .. } => CecOpcode::SelectAnalogueService,
+            CecMessage::SelectDigitalService { .. } => CecOpcode::SelectDigitalService,
+            CecMessage::TunerDeviceStatus { .. } => CecOpcode::TunerDeviceStatus,
+            CecMessage::TunerStepDecrement => CecOpcode::TunerStepDecrement,
+            CecMessage::TunerStepIncrement => CecOpcode::TunerStepIncrement,
+            CecMessage::DeviceVendorId { .. } => CecOpcode::DeviceVendorId,
+            CecMessage::GiveDeviceVendorId => CecOpcode::GiveDeviceVendorId,
+            CecMessage::VendorCommand { .. } => CecOpcode::VendorCommand,
+            CecMessage::VendorCommandWithId { .. } => CecOpcode::VendorCommandWithId,
+            CecMessage::VendorRemoteButtonDown { .. } => CecOpcode::VendorRemoteButtonDown,
+            CecMessage::VendorRemoteButtonUp => CecOpcode::VendorRemoteButtonUp,
+            CecMessage::SetOsdString { .. } => CecOpcode::SetOsdString,
+            CecMessage::GiveOsdName => CecOpcode::GiveOsdName,
+            CecMessage::SetOsdName { .. } => CecOpcode::SetOsdName,
+            CecMessage::MenuRequest { .. } => CecOpcode::MenuRequest,
+            CecMessage::MenuStatus { .. } => CecOpcode::MenuStatus,
+            CecMessage::UserControlPressed { .. } => CecOpcode::UserControlPressed,
+            CecMessage::UserControlRelease => CecOpcode::UserControlRelease,
+            CecMessage::GiveDevicePowerStatus => CecOpcode::GiveDevicePowerStatus,
+            CecMessage::ReportPowerStatus { .. } => CecOpcode::ReportPowerStatus,
+            CecMessage::FeatureAbort { .. } => CecOpcode::FeatureAbort,
+            CecMessage::Abort => CecOpcode::Abort,
+            CecMessage::GiveAudioStatus => CecOpcode::GiveAudioStatus,
+            CecMessage::GiveSystemAudioModeStatus => CecOpcode::GiveSystemAudioModeStatus,
+            CecMessage::ReportAudioStatus { .. } => CecOpcode::ReportAudioStatus,
+            CecMessage::SetSystemAudioMode { .. } => CecOpcode::SetSystemAudioMode,
+            CecMessage::SystemAudioModeRequest { .. } => CecOpcode::SystemAudioModeRequest,
+            CecMessage::SystemAudioModeStatus { .. } => CecOpcode::SystemAudioModeStatus,
+            CecMessage::SetAudioRate { .. } => CecOpcode::SetAudioRate,
+            CecMessage::ReportShortAudioDescriptors { .. } => {
+                CecOpcode::ReportShortAudioDescriptors
+            }
+            CecMessage::RequestShortAudioDescriptors { .. } => {
+                CecOpcode::RequestShortAudioDescriptors
+            }
+            CecMessage::StartArc => CecOpcode::StartArc,
+            CecMessage::ReportArcStarted => CecOpcode::ReportArcStarted,
+            CecMessage::ReportArcEnded => CecOpcode::ReportArcEnded,
+            CecMessage::RequestArcStart => CecOpcode::RequestArcStart,
+            CecMessage::RequestArcEnd => CecOpcode::RequestArcEnd,
+            CecMessage::EndArc => CecOpcode::EndArc,
+            CecMessage::Cdc { .. } => CecOpcode::Cdc,
+        }
+    }
+
+    /// Encode the operands of the message
+    ///
+    /// Strings longer than allowed by the CEC specification are truncated.
+    pub fn parameters(&self) -> CecDatapacket {
+        let mut p = ArrayVec::new();
+        match self {
+            CecMessage::ActiveSource { address }
+            | CecMessage::InactiveSource { address }
+            | CecMessage::RoutingInformation { address }
+            | CecMessage::SetStreamPath { address } => push_physical_address(&mut p, *address),
+            CecMessage::RoutingChange {
+                original_address,
+                new_address,
+            } => {
+                push_physical_address(&mut p, *original_address);
+                push_physical_address(&mut p, *new_address);
+            }
+            CecMessage::RecordOn { source } => push_record_source(&mut p, source),
+            CecMessage::RecordStatus { status } => p.push(status.repr() as u8),
+            CecMessage::ClearAnalogueTimer { timer, service }
+            | CecMessage::SetAnalogueTimer { timer, service } => {
+                push_timer_info(&mut p, timer);
+                push_analogue_service(&mut p, service);
+            }
+            CecMessage::ClearDigitalTimer { timer, service }
+            | CecMessage::SetDigitalTimer { timer, service } => {
+                push_timer_info(&mut p, timer);
+                p.extend(service.iter().copied());
+            }
+            CecMessage::ClearExternalTimer { timer, source }
+            | CecMessage::SetExternalTimer { timer, source } => {
+                push_timer_info(&mut p, timer);
+                push_external_source(&mut p, source);
+            }
+            CecMessage::SetTimerProgramTitle { title } => {
+                push_str(&mut p, title, PROGRAM_TITLE_MAX_LENGTH)
+            }
+            CecMessage::TimerClearedStatus { status } => p.push(status.repr() as u8),
+            CecMessage::TimerStatus { status } => push_timer_status(&mut p, status),
+            CecMessage::CecVersion { version } => p.push(version.repr() as u8),
+            CecMessage::ReportPhysicalAddress {
+                address,
+                device_type,
+            } => {
+                push_physical_address(&mut p, *address);
+                p.push(device_type.repr() as u8);
+            }
+            CecMessage::SetMenuLanguage { language } => push_str(&mut p, language.as_str(), 3),
+            CecMessage::DeckControl { mode } => p.push(mode.repr() as u8),
+            CecMessage::DeckStatus { info } => p.push(info.repr() as u8),
+            CecMessage::GiveDeckStatus { request }
+            | CecMessage::GiveTunerDeviceStatus { request } => p.push(request.repr() as u8),
+            CecMessage::Play { mode } => p.push(mode.repr() as u8),
+            CecMessage::SelectAnalogueService { service } => push_analogue_service(&mut p, service),
+            CecMessage::SelectDigitalService { service } => p.extend(service.iter().copied()),
+            CecMessage::TunerDeviceStatus {
+                recording_flag,
+                display_info,
+                service,
+            } => {
+                p.push((recording_flag.repr() as u8) << 7 | (display_info.repr() as u8 & 0x7F));
+                match service {
+                    Some(CecServiceId::Analogue(service)) => push_analogue_service(&mut p, service),
+                    Some(CecServiceId::Digital(service)) => p.extend(service.iter().copied()),
+                    None => {}
+                }
+            }
+            CecMessage::DeviceVendorId { vendor_id } => push_vendor_id(&mut p, *vendor_id),
+            CecMessage::VendorCommand { data } => push_datapacket(&mut p, data),
+            CecMessage::VendorCommandWithId { vendor_id, data } => {
+                push_vendor_id(&mut p, *vendor_id);
+                push_datapacket(&mut p, data);
+            }
+            CecMessage::VendorRemoteButtonDown { rc_code } => push_datapacket(&mut p, rc_code),
+            CecMessage::SetOsdString {
+                display_control,
+                text,
+            } => {
+                p.push(display_control.repr() as u8);
+                push_str(&mut p, text, OSD_STRING_MAX_LENGTH);
+            }
+            CecMessage::SetOsdName { name } => push_str(&mut p, name, OSD_NAME_MAX_LENGTH),
+            CecMessage::MenuRequest { request } => p.push(request.repr() as u8),
+            CecMessage::MenuStatus { state } => p.push(state.repr() as u8),
+            CecMessage::UserControlPressed { keycode } => p.push(keycode.repr() as u8),
+            CecMessage::ReportPowerStatus { status } => p.push(status.repr() as u8),
+            CecMessage::FeatureAbort { opcode, reason } => {
+                p.push(opcode.repr() as u8);
+                p.push(reason.repr() as u8);
+            }
+            CecMessage::ReportAudioStatus { status } => p.push(u8::from(*status)),
+            CecMessage::SetSystemAudioMode { status }
+            | CecMessage::SystemAudioModeStatus { status } => p.push(status.repr() as u8),
+            CecMessage::SystemAudioModeRequest { address } => {
+                if let Some(address) = address {
+                    push_physical_address(&mut p, *address);
+                }
+            }
+            CecMessage::SetAudioRate { rate } => p.push(rate.repr() as u8),
+            CecMessage::ReportShortAudioDescriptors { descriptors } => {
+                for descriptor in descriptors {
+                    p.extend(descriptor.iter().copied());
+                }
+            }
+            CecMessage::RequestShortAudioDescriptors { formats } => {
+                p.extend(formats.iter().copied())
+            }
+            CecMessage::Cdc { initiator, data } => {
+                push_physical_address(&mut p, *initiator);
+                push_datapacket(&mut p, data);
+            }
+            CecMessage::Poll
+            | CecMessage::ImageViewOn
+            | CecMessage::TextViewOn
+            | CecMessage::RequestActiveSource
+            | CecMessage::Standby
+            | CecMessage::RecordOff
+            | CecMessage::RecordTvScreen
+            | CecMessage::GetCecVersion
+            | CecMessage::GivePhysicalAddress
+            | CecMessage::GetMenuLanguage
+            | CecMessage::TunerStepDecrement
+            | CecMessage::TunerStepIncrement
+            | CecMessage::GiveDeviceVendorId
+            | CecMessage::VendorRemoteButtonUp
+            | CecMessage::GiveOsdName
+            | CecMessage::UserControlRelease
+            | CecMessage::GiveDevicePowerStatus
+            | CecMessage::Abort
+            | CecMessage::GiveAudioStatus
+            | CecMessage::GiveSystemAudioModeStatus
+            | CecMessage::StartArc
+            | CecMessage::ReportArcStarted
+            | CecMessage::ReportArcEnded
+            | CecMessage::RequestArcStart
+            | CecMessage::RequestArcEnd
+            | CecMessage::EndArc => {}
+        }
+        CecDatapacket(p)
+    }
+
+    /// Encode message into command with given addressing
+    ///
+    /// Uses `DEFAULT_TRANSMIT_TIMEOUT` as transmit timeout.
+    pub fn to_command(
+        &self,
+        initiator: CecLogicalAddress,
+        destination: CecLogicalAddress,
+    ) -> CecCommand {
+        CecCommand {
+            initiator,
+            destination,
+            ack: false,
+            eom: false,
+            opcode: self.opcode(),
+            parameters: self.parameters(),
+            opcode_set: *self != CecMessage::Poll,
+            transmit_timeout: DEFAULT_TRANSMIT_TIMEOUT,
+        }
+    }
+}
+
+impl TryFrom<&CecCommand> for CecMessage {
+    type Error = CecMessageError;
+
+    fn try_from(command: &CecCommand) -> Result<Self, Self::Error> {
+        if !command.opcode_set {
+            return Ok(CecMessage::Poll);
+        }
+        CecMessage::decode(command.opcode, command.parameters.0.as_slice())
+    }
+}
+
+impl CecCommand {
+    /// Decode opcode and parameters of this command into `CecMessage`
+    pub fn message(&self) -> Result<CecMessage, CecMessageError> {
+        CecMessage::try_from(self)
+    }
+}
+
+#[cfg(test)]
+mod message_tests {
+    use super::*;
+
+    fn assert_roundtrip(message: CecMessage, raw: &[u8]) {
+        let command = message.to_command(CecLogicalAddress::Playbackdevice1, CecLogicalAddress::Tv);
+        assert_eq!(command.opcode, message.opcode());
+        assert_eq!(command.parameters.0.as_slice(), raw, "{:?}", message);
+        assert_eq!(command.message(), Ok(message));
+    }
+
+    fn addr(s: &str) -> CecPhysicalAddress {
+        s.parse().unwrap()
+    }
+
+    fn packet(raw: &[u8]) -> CecDatapacket {
+        let mut packet = CecDatapacket(ArrayVec::new());
+        packet.0.try_extend_from_slice(raw).unwrap();
+        packet
+    }
+
+    #[test]
+    fn test_poll() {
+        let command = CecMessage::Poll.to_command(CecLogicalAddress::Tv, CecLogicalAddress::Tv);
+        assert!(!command.opcode_set);
+        assert_eq!(command.message(), Ok(CecMessage::Poll));
+    }
+
+    #[test]
+    fn test_no_operands() {
+        assert_roundtrip(CecMessage::ImageViewOn, &[]);
+        assert_roundtrip(CecMessage::GiveOsdName, &[]);
+        assert_roundtrip(CecMessage::GiveDevicePowerStatus, &[]);
+        assert_roundtrip(CecMessage::Standby, &[]);
+    }
+
+    #[test]
+    fn test_physical_address_operands() {
+        assert_roundtrip(
+            CecMessage::ActiveSource {
+                address: addr("1.2.0.0"),
+            },
+            &[0x12, 0x00],
+        );
+        assert_roundtrip(
+            CecMessage::RoutingChange {
+                original_address: addr("1.0.0.0"),
+                new_address: addr("2.1.0.0"),
+            },
+            &[0x10, 0x00, 0x21, 0x00],
+        );
+        assert_roundtrip(
+            CecMessage::ReportPhysicalAddress {
+                address: addr("3.0.0.0"),
+                device_type: CecDeviceType::PlaybackDevice,
+            },
+            &[0x30, 0x00, CecDeviceType::PlaybackDevice.repr() as u8],
+        );
+        assert_roundtrip(CecMessage::SystemAudioModeRequest { address: None }, &[]);
+    }
+
+    #[test]
+    fn test_enum_operands() {
+        assert_roundtrip(
+            CecMessage::ReportPowerStatus {
+                status: CecPowerStatus::Standby,
+            },
+            &[CecPowerStatus::Standby.repr() as u8],
+        );
+        assert_roundtrip(
+            CecMessage::DeckStatus {
+                info: CecDeckInfo::Play,
+            },
+            &[CecDeckInfo::Play.repr() as u8],
+        );
+        assert_roundtrip(
+            CecMessage::FeatureAbort {
+                opcode: CecOpcode::GiveDeckStatus,
+                reason: CecAbortReason::Refused,
+            },
+            &[
+                CecOpcode::GiveDeckStatus.repr() as u8,
+                CecAbortReason::Refused.repr() as u8,
+            ],
+        );
+        assert_roundtrip(
+            CecMessage::ReportAudioStatus {
+                status: KnownCecAudioStatus::new(42, true),
+            },
+            &[0x80 | 42],
+        );
+    }
+
+    #[test]
+    fn test_vendor_id() {
+        let raw = CecVendorId::PulseEight.repr();
+        assert_roundtrip(
+            CecMessage::DeviceVendorId {
+                vendor_id: CecVendorId::PulseEight,
+            },
+            &[(raw >> 16) as u8, (raw >> 8) as u8, raw as u8],
+        );
+        assert_roundtrip(
+            CecMessage::VendorCommandWithId {
+                vendor_id: CecVendorId::PulseEight,
+                data: packet(&[1, 2, 3]),
+            },
+            &[(raw >> 16) as u8, (raw >> 8) as u8, raw as u8, 1, 2, 3],
+        );
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_roundtrip(
+            CecMessage::SetOsdName {
+                name: "Kodi".into(),
+            },
+            b"Kodi",
+        );
+        assert_roundtrip(
+            CecMessage::SetMenuLanguage {
+                language: CecMenuLanguage::new("fin").unwrap(),
+            },
+            b"fin",
+        );
+        let name = CecMessage::SetOsdName {
+            name: "A very long OSD name".into(),
+        };
+        assert_eq!(name.parameters().0.as_slice(), b"A very long OS");
+    }
+
+    #[test]
+    fn test_timers() {
+        let timer = CecTimerInfo {
+            day_of_month: 24,
+            month: 12,
+            start_hour: 21,
+            start_minute: 30,
+            duration_hours: 1,
+            duration_minutes: 45,
+            recording_sequence: 0,
+        };
+        assert_roundtrip(
+            CecMessage::SetExternalTimer {
+                timer,
+                source: CecExternalSource::PhysicalAddress(addr("1.1.0.0")),
+            },
+            &[
+                24,
+                12,
+                0x21,
+                0x30,
+                0x01,
+                0x45,
+                0,
+                CecExternalSourceSpecifier::PhysicalAddress.repr() as u8,
+                0x11,
+                0x00,
+            ],
+        );
+        assert_roundtrip(
+            CecMessage::ClearDigitalTimer {
+                timer,
+                service: [1, 2, 3, 4, 5, 6, 7],
+            },
+            &[24, 12, 0x21, 0x30, 0x01, 0x45, 0, 1, 2, 3, 4, 5, 6, 7],
+        );
+        assert_eq!(
+            CecMessage::decode(
+                CecOpcode::ClearDigitalTimer,
+                &[24, 12, 0x2A, 0x30, 0x01, 0x45, 0, 1, 2, 3, 4, 5, 6, 7]
+            ),
+            Err(CecMessageError::InvalidOperand {
+                opcode: CecOpcode::ClearDigitalTimer,
+                position: 2,
+                value: 0x2A
+            })
+        );
+    }
+
+    #[test]
+    fn test_record_source() {
+        let service = CecAnalogueService {
+            broadcast_type: CecAnalogueBroadcastType::Cable,
+            frequency: 0x1234,
+            broadcast_system: CecBroadcastSystem::PalBG,
+        };
+        let message = CecMessage::RecordOn {
+            source: CecRecordSource::AnalogueService(service),
+        };
+        assert_eq!(message.parameters().0.len(), 5);
+        assert_roundtrip(message.clone(), message.parameters().0.as_slice());
+        let message = CecMessage::RecordOn {
+            source: CecRecordSource::OwnSource,
+        };
+        assert_roundtrip(message.clone(), message.parameters().0.as_slice());
+    }
+
+    #[test]
+    fn test_short_audio_descriptors() {
+        let mut descriptors = ArrayVec::new();
+        descriptors.push([1, 2, 3]);
+        descriptors.push([4, 5, 6]);
+        assert_roundtrip(
+            CecMessage::ReportShortAudioDescriptors { descriptors },
+            &[1, 2, 3, 4, 5, 6],
+        );
+    }
+
+    #[test]
+    fn test_additional_operands_ignored() {
+        assert_eq!(
+            CecMessage::decode(CecOpcode::SetStreamPath, &[0x10, 0x00, 0xAB]),
+            Ok(CecMessage::SetStreamPath {
+                address: addr("1.0.0.0")
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_parameters() {
+        assert_eq!(
+            CecMessage::decode(CecOpcode::ActiveSource, &[0x10]),
+            Err(CecMessageError::MissingParameters {
+                opcode: CecOpcode::ActiveSource,
+                length: 1
+            })
+        );
+        assert_eq!(
+            CecMessage::decode(CecOpcode::SetOsdName, &[]),
+            Err(CecMessageError::MissingParameters {
+                opcode: CecOpcode::SetOsdName,
+                length: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_codes() {
+        // CEC 2.0 codes not known to libcec
+        assert_roundtrip(
+            CecMessage::UserControlPressed {
+                keycode: CecUserControlCode::from(0x77),
+            },
+            &[0x77],
+        );
+        assert_roundtrip(
+            CecMessage::FeatureAbort {
+                opcode: CecOpcode::Other(0xA7),
+                reason: CecAbortReason::UnrecognizedOpcode,
+            },
+            &[0xA7, CecAbortReason::UnrecognizedOpcode.repr() as u8],
+        );
+        assert_roundtrip(
+            CecMessage::DeviceVendorId {
+                vendor_id: CecVendorId::Other(0x123456),
+            },
+            &[0x12, 0x34, 0x56],
+        );
+        assert_eq!(
+            CecMessage::decode(CecOpcode::Other(0xA7), &[]),
+            Err(CecMessageError::UnknownOpcode {
+                opcode: CecOpcode::Other(0xA7)
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_operand() {
+        assert_eq!(
+            CecMessage::decode(CecOpcode::ReportPowerStatus, &[0x42]),
+            Err(CecMessageError::InvalidOperand {
+                opcode: CecOpcode::ReportPowerStatus,
+                position: 0,
+                value: 0x42
+            })
+        );
+        assert_eq!(
+            CecMessage::decode(CecOpcode::ActiveSource, &[0xFF, 0xFF]),
+            Err(CecMessageError::InvalidOperand {
+                opcode: CecOpcode::ActiveSource,
+                position: 0,
+                value: 0xFF
+            })
+        );
+    }
+}
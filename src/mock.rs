@@ -0,0 +1,1204 @@
+use crate::backend::CecBackend;
+use crate::{
+    CecAdapterInfo, CecCallbacks, CecCommand, CecCommandBuildError, CecConfiguration,
+    CecConfigurationUpdate, CecConnection, CecConnectionCfg, CecConnectionResult,
+    CecConnectionResultError, CecDeckControlMode, CecDeckInfo, CecDeviceType, CecEvent,
+    CecLogicalAddress, CecLogicalAddresses, CecMenuLanguage, CecMessage, CecPhysicalAddress,
+    CecPowerStatus, CecUserControlCode, CecVendorId, CecVersion, FnEvent, KnownCecAudioStatus,
+    KnownCecLogicalAddress, TryFromCecAudioStatusError, TryFromCecLogicalAddressesError,
+};
+
+use libcec_sys::libcec_configuration;
+use log::warn;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError};
+
+/// Commands sent on the bus as a consequence of a single transmit, to stop devices
+/// replying to each other forever
+const MAX_COMMANDS_PER_TRANSMIT: usize = 64;
+
+/// Device on a `CecMockBus`
+///
+/// The device receives broadcast commands and commands sent to its logical address,
+/// and acknowledges the latter.
+pub trait CecMockDevice: Send {
+    fn logical_address(&self) -> CecLogicalAddress;
+
+    /// Receive `command` sent by another device. Returns the commands to send in reply,
+    /// an `Unknown` initiator is replaced with the logical address of this device.
+    fn receive(&mut self, command: &CecCommand) -> Vec<CecCommand>;
+}
+
+/// Simulated CEC bus, for testing without a CEC adapter
+///
+/// Open a `CecConnection` on the bus with `CecMockBus::open`. Commands transmitted by the
+/// connection are recorded, and delivered to the devices added with `add_device`.
+/// Commands injected with `inject` are delivered to the connection callbacks when sent
+/// to the connection or broadcast.
+///
+/// Queries such as `CecConnection::get_device_power_status` send the request on the bus
+/// and return the reply of the device, like libcec does. The bus is synchronous: replies
+/// are sent before `transmit` returns.
+#[derive(Clone, Default)]
+pub struct CecMockBus(Arc<MockBusShared>);
+
+#[derive(Default)]
+struct MockBusShared {
+    state: Mutex<MockBusState>,
+    callbacks: Mutex<Option<CecCallbacks>>,
+}
+
+struct MockBusState {
+    devices: Vec<Box<dyn CecMockDevice>>,
+    transmitted: Vec<CecCommand>,
+    pending: VecDeque<CecEvent>,
+    connected: bool,
+    logical_address: CecLogicalAddress,
+    physical_address: CecPhysicalAddress,
+    active_source: CecLogicalAddress,
+    configuration: Option<CecConfiguration>,
+}
+
+impl Default for MockBusState {
+    fn default() -> Self {
+        MockBusState {
+            devices: Vec::new(),
+            transmitted: Vec::new(),
+            pending: VecDeque::new(),
+            connected: false,
+            logical_address: CecLogicalAddress::Unregistered,
+            physical_address: DEFAULT_PHYSICAL_ADDRESS,
+            active_source: CecLogicalAddress::Unknown,
+            configuration: None,
+        }
+    }
+}
+
+/// Physical address of the connection when not configured, first HDMI input of the TV
+const DEFAULT_PHYSICAL_ADDRESS: CecPhysicalAddress = CecPhysicalAddress(0x1000);
+
+/// Logical addresses that a device of `device_type` may claim, in order of preference
+fn candidate_addresses(device_type: CecDeviceType) -> &'static [CecLogicalAddress] {
+    match device_type {
+        CecDeviceType::Tv => &[CecLogicalAddress::Tv],
+        CecDeviceType::RecordingDevice => &[
+            CecLogicalAddress::Recordingdevice1,
+            CecLogicalAddress::Recordingdevice2,
+            CecLogicalAddress::Recordingdevice3,
+        ],
+        CecDeviceType::Tuner => &[
+            CecLogicalAddress::Tuner1,
+            CecLogicalAddress::Tuner2,
+            CecLogicalAddress::Tuner3,
+            CecLogicalAddress::Tuner4,
+        ],
+        CecDeviceType::PlaybackDevice => &[
+            CecLogicalAddress::Playbackdevice1,
+            CecLogicalAddress::Playbackdevice2,
+            CecLogicalAddress::Playbackdevice3,
+        ],
+        CecDeviceType::AudioSystem => &[CecLogicalAddress::Audiosystem],
+        CecDeviceType::Reserved => &[],
+    }
+}
+
+impl MockBusState {
+    fn is_taken(&self, address: CecLogicalAddress) -> bool {
+        self.devices
+            .iter()
+            .any(|device| device.logical_address() == address)
+    }
+
+    /// Send `command` and the replies of the devices. Returns whether `command` was
+    /// acknowledged, and the replies
+    fn send(&mut self, command: CecCommand) -> (bool, Vec<CecCommand>) {
+        let mut queue = VecDeque::new();
+        let acked = self.route(&command, &mut queue);
+        let mut replies = Vec::new();
+        while let Some(reply) = queue.pop_front() {
+            if replies.len() == MAX_COMMANDS_PER_TRANSMIT {
+                warn!("CecMockBus: too many replies, dropping {:?}", reply);
+                break;
+            }
+            self.route(&reply, &mut queue);
+            replies.push(reply);
+        }
+        (acked, replies)
+    }
+
+    /// Deliver `command` to its receivers, queueing their replies. Returns whether
+    /// `command` was acknowledged
+    fn route(&mut self, command: &CecCommand, replies: &mut VecDeque<CecCommand>) -> bool {
+        self.observe(command);
+        let broadcast = command.destination == CecLogicalAddress::Unregistered;
+        let mut acked = broadcast;
+        for device in self.devices.iter_mut() {
+            let address = device.logical_address();
+            if address == command.initiator || !(broadcast || address == command.destination) {
+                continue;
+            }
+            acked = true;
+            replies.extend(device.receive(command).into_iter().map(|mut reply| {
+                if reply.initiator == CecLogicalAddress::Unknown {
+                    reply.initiator = address;
+                }
+                reply
+            }));
+        }
+        if self.connected
+            && command.initiator != self.logical_address
+            && (broadcast || command.destination == self.logical_address)
+        {
+            acked = true;
+            self.pending.push_back(CecEvent::Command(command.clone()));
+        }
+        acked
+    }
+
+    /// Track the active source, like libcec does
+    fn observe(&mut self, command: &CecCommand) {
+        let active_source = match command.message() {
+            Ok(CecMessage::ActiveSource { .. }) => command.initiator,
+            Ok(CecMessage::InactiveSource { .. }) if command.initiator == self.active_source => {
+                CecLogicalAddress::Unknown
+            }
+            _ => return,
+        };
+        let was_active = self.active_source == self.logical_address;
+        let is_active = active_source == self.logical_address;
+        self.active_source = active_source;
+        if self.connected && was_active != is_active {
+            self.pending.push_back(CecEvent::SourceActivated {
+                address: self.logical_address,
+                activated: is_active,
+            });
+        }
+    }
+}
+
+impl CecMockBus {
+    pub fn new() -> CecMockBus {
+        CecMockBus::default()
+    }
+
+    /// Add a device to the bus
+    pub fn add_device<D: CecMockDevice + 'static>(&self, device: D) {
+        self.state().devices.push(Box::new(device));
+    }
+
+    /// Open a connection on the bus, see `CecConnectionCfg::open`
+    ///
+    /// The connection claims the first free logical address for its first device type.
+    ///
+    /// # Errors
+    ///
+    /// - AdapterOpenFailed: a connection is already open on the bus
+    /// - CommandHandlerUnsupported: command_handler_callback is set, but libcec is older than 7
+    pub fn open(&self, config: CecConnectionCfg) -> CecConnectionResult<CecConnection> {
+        self.open_with_event_callback(config, None)
+    }
+
+    /// Open connection on the bus, additionally reporting all events to `event_callback`
+    pub(crate) fn open_with_event_callback(
+        &self,
+        mut config: CecConnectionCfg,
+        event_callback: Option<Box<FnEvent>>,
+    ) -> CecConnectionResult<CecConnection> {
+        config.check_callbacks()?;
+        {
+            let mut state = self.state();
+            if state.connected {
+                return Err(CecConnectionResultError::AdapterOpenFailed {
+                    port: "CecMockBus".into(),
+                });
+            }
+            let device_type = config.device_types.0.first().copied();
+            state.logical_address = device_type
+                .map(candidate_addresses)
+                .unwrap_or_default()
+                .iter()
+                .copied()
+                .find(|address| !state.is_taken(*address))
+                .unwrap_or(CecLogicalAddress::Unregistered);
+            state.physical_address = config.physical_address.unwrap_or(DEFAULT_PHYSICAL_ADDRESS);
+            state.configuration =
+                Some(CecConfiguration::from(&libcec_configuration::from(&config)));
+            state.connected = true;
+        }
+        let callbacks = CecCallbacks::take_from(&mut config, event_callback);
+        let command_watchers = callbacks.command_watchers.clone();
+        *self.callbacks() = Some(callbacks);
+        Ok(CecConnection(
+            config,
+            Box::new(MockBackend(self.clone())),
+            command_watchers,
+        ))
+    }
+
+    /// Commands transmitted by the connection so far
+    pub fn transmitted(&self) -> Vec<CecCommand> {
+        self.state().transmitted.clone()
+    }
+
+    /// Commands transmitted by the connection so far, clearing the record
+    pub fn take_transmitted(&self) -> Vec<CecCommand> {
+        std::mem::take(&mut self.state().transmitted)
+    }
+
+    /// Send `command` on the bus, as if sent by another device
+    ///
+    /// Returns whether the command was acknowledged.
+    pub fn inject(&self, command: CecCommand) -> bool {
+        let (acked, _) = self.state().send(command);
+        self.dispatch();
+        acked
+    }
+
+    /// Report `event` to the connection callbacks, e.g. a keypress or an alert
+    pub fn inject_event(&self, event: CecEvent) {
+        self.state().pending.push_back(event);
+        self.dispatch();
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockBusState> {
+        self.0.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn callbacks(&self) -> MutexGuard<'_, Option<CecCallbacks>> {
+        self.0
+            .callbacks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Call the callbacks for pending events
+    ///
+    /// When the callbacks are already running, e.g. a callback transmits a command,
+    /// the running dispatch delivers the events once the callback returns.
+    fn dispatch(&self) {
+        loop {
+            let mut callbacks = match self.0.callbacks.try_lock() {
+                Ok(callbacks) => callbacks,
+                Err(TryLockError::Poisoned(err)) => err.into_inner(),
+                Err(TryLockError::WouldBlock) => return,
+            };
+            loop {
+                let event = self.state().pending.pop_front();
+                let event = match event {
+                    Some(event) => event,
+                    None => break,
+                };
+                if let Some(callbacks) = callbacks.as_mut() {
+                    #[cfg(abi7)]
+                    if let CecEvent::Command(command) = &event {
+                        callbacks.handle_command(command.clone());
+                    }
+                    callbacks.event(event);
+                }
+            }
+            drop(callbacks);
+            // Events queued after the last check, while the callbacks were locked
+            if self.state().pending.is_empty() {
+                return;
+            }
+        }
+    }
+}
+
+/// Connection on a `CecMockBus`
+struct MockBackend(CecMockBus);
+
+impl MockBackend {
+    /// Send `command` from the connection. Returns whether it was acknowledged, and
+    /// the replies
+    ///
+    /// An `Unknown` initiator is replaced with the logical address of the connection,
+    /// like libcec does for the commands it builds itself, e.g. for `poll_device`.
+    fn send(&self, mut command: CecCommand) -> (bool, Vec<CecCommand>) {
+        let result = {
+            let mut state = self.0.state();
+            if command.initiator == CecLogicalAddress::Unknown {
+                command.initiator = state.logical_address;
+            }
+            state.transmitted.push(command.clone());
+            state.send(command)
+        };
+        self.0.dispatch();
+        result
+    }
+
+    fn send_acked(&self, command: CecCommand) -> CecConnectionResult<()> {
+        let destination = command.destination;
+        match self.send(command) {
+            (true, _) => Ok(()),
+            (false, _) => Err(CecConnectionResultError::NotAcknowledged { destination }),
+        }
+    }
+
+    /// Send `request` to `address`, and return the first reply of `address` that
+    /// `parse` accepts
+    fn request<T>(
+        &self,
+        address: CecLogicalAddress,
+        request: Result<CecCommand, CecCommandBuildError>,
+        parse: impl Fn(CecMessage) -> Option<T>,
+    ) -> Option<T> {
+        self.request_result(address, request, parse).ok()
+    }
+
+    /// Like `request`, failing when `address` does not acknowledge the request, aborts it
+    /// or does not reply
+    fn request_result<T>(
+        &self,
+        address: CecLogicalAddress,
+        request: Result<CecCommand, CecCommandBuildError>,
+        parse: impl Fn(CecMessage) -> Option<T>,
+    ) -> CecConnectionResult<T> {
+        let request = request?;
+        let opcode = request.opcode;
+        let (acked, replies) = self.send(request);
+        if !acked {
+            return Err(CecConnectionResultError::NotAcknowledged {
+                destination: address,
+            });
+        }
+        let mut error = CecConnectionResultError::Timeout {
+            destination: address,
+        };
+        for reply in replies.iter().filter(|reply| reply.initiator == address) {
+            match reply.message() {
+                Ok(CecMessage::FeatureAbort {
+                    opcode: aborted,
+                    reason,
+                }) if aborted == opcode => {
+                    error = CecConnectionResultError::FeatureAbort {
+                        destination: address,
+                        opcode,
+                        reason,
+                    }
+                }
+                Ok(message) => {
+                    if let Some(value) = parse(message) {
+                        return Ok(value);
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+        Err(error)
+    }
+
+    fn keypress(&self, address: CecLogicalAddress, key: CecUserControlCode, release: bool) {
+        let _ = self.send_keypress(address, key, false);
+        if release {
+            let _ = self.send_key_release(address, false);
+        }
+    }
+}
+
+impl CecBackend for MockBackend {
+    fn transmit(&self, command: CecCommand) -> CecConnectionResult<()> {
+        // libcec refuses to send from an unknown initiator
+        if command.initiator == CecLogicalAddress::Unknown {
+            return Err(CecConnectionResultError::TransmitFailed);
+        }
+        self.send_acked(command)
+    }
+
+    fn send_power_on_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        match address {
+            CecLogicalAddress::Tv | CecLogicalAddress::Unregistered => {
+                self.send_acked(CecCommand::image_view_on(CecLogicalAddress::Tv).unwrap())
+            }
+            _ => {
+                self.send_keypress(address, CecUserControlCode::PowerOnFunction, false)?;
+                self.send_key_release(address, false)
+            }
+        }
+    }
+
+    fn send_standby_devices(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        let command = CecCommand::standby(address)?;
+        self.send_acked(command)
+    }
+
+    fn set_active_source(&self, _device_type: CecDeviceType) -> CecConnectionResult<()> {
+        let _ = self.send(CecCommand::image_view_on(CecLogicalAddress::Tv).unwrap());
+        let physical_address = self.0.state().physical_address;
+        self.send_acked(CecCommand::active_source(physical_address))
+    }
+
+    fn get_active_source(&self) -> CecLogicalAddress {
+        self.0.state().active_source
+    }
+
+    fn is_active_source(&self, address: CecLogicalAddress) -> bool {
+        self.0.state().active_source == address
+    }
+
+    fn get_device_power_status(&self, address: CecLogicalAddress) -> CecPowerStatus {
+        self.request(
+            address,
+            CecCommand::give_device_power_status(address),
+            |message| match message {
+                CecMessage::ReportPowerStatus { status } => Some(status),
+                _ => None,
+            },
+        )
+        .unwrap_or(CecPowerStatus::Unknown)
+    }
+
+    fn send_keypress(
+        &self,
+        address: CecLogicalAddress,
+        key: CecUserControlCode,
+        _wait: bool,
+    ) -> CecConnectionResult<()> {
+        let command = CecCommand::user_control_pressed(address, key)?;
+        self.send_acked(command)
+    }
+
+    fn send_key_release(&self, address: CecLogicalAddress, _wait: bool) -> CecConnectionResult<()> {
+        let command = CecCommand::user_control_release(address)?;
+        self.send_acked(command)
+    }
+
+    fn volume_up(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        self.keypress(
+            CecLogicalAddress::Audiosystem,
+            CecUserControlCode::VolumeUp,
+            send_release,
+        );
+        self.audio_get_status()
+    }
+
+    fn volume_down(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        self.keypress(
+            CecLogicalAddress::Audiosystem,
+            CecUserControlCode::VolumeDown,
+            send_release,
+        );
+        self.audio_get_status()
+    }
+
+    fn mute_audio(
+        &self,
+        send_release: bool,
+    ) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        self.keypress(
+            CecLogicalAddress::Audiosystem,
+            CecUserControlCode::Mute,
+            send_release,
+        );
+        self.audio_get_status()
+    }
+
+    fn audio_toggle_mute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        self.mute_audio(true)
+    }
+
+    fn audio_mute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        let status = self.audio_get_status()?;
+        if status.is_muted() {
+            Ok(status)
+        } else {
+            self.audio_toggle_mute()
+        }
+    }
+
+    fn audio_unmute(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        let status = self.audio_get_status()?;
+        if status.is_muted() {
+            self.audio_toggle_mute()
+        } else {
+            Ok(status)
+        }
+    }
+
+    fn audio_get_status(&self) -> Result<KnownCecAudioStatus, TryFromCecAudioStatusError> {
+        self.request(
+            CecLogicalAddress::Audiosystem,
+            CecCommand::give_audio_status(CecLogicalAddress::Audiosystem),
+            |message| match message {
+                CecMessage::ReportAudioStatus { status } => Some(status),
+                _ => None,
+            },
+        )
+        .ok_or(TryFromCecAudioStatusError::Unknown)
+    }
+
+    fn set_inactive_view(&self) -> CecConnectionResult<()> {
+        let (is_active, physical_address) = {
+            let state = self.0.state();
+            (
+                state.active_source == state.logical_address,
+                state.physical_address,
+            )
+        };
+        if is_active {
+            self.send_acked(CecCommand::inactive_source(physical_address))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_logical_address(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        self.0.state().logical_address = address;
+        Ok(())
+    }
+
+    fn get_logical_addresses(
+        &self,
+    ) -> Result<CecLogicalAddresses, TryFromCecLogicalAddressesError> {
+        Ok(
+            match KnownCecLogicalAddress::new(self.0.state().logical_address) {
+                Some(address) => CecLogicalAddresses::with_only_primary(&address),
+                None => CecLogicalAddresses::default(),
+            },
+        )
+    }
+
+    fn switch_monitoring(&self, _enable: bool) -> CecConnectionResult<()> {
+        Ok(())
+    }
+
+    fn set_deck_control_mode(
+        &self,
+        _mode: CecDeckControlMode,
+        _send_update: bool,
+    ) -> CecConnectionResult<()> {
+        Ok(())
+    }
+
+    fn set_deck_info(&self, info: CecDeckInfo, send_update: bool) -> CecConnectionResult<()> {
+        if send_update {
+            let _ = self.send(CecCommand::deck_status(CecLogicalAddress::Tv, info).unwrap());
+        }
+        Ok(())
+    }
+
+    fn get_device_cec_version(&self, address: CecLogicalAddress) -> CecVersion {
+        self.request(
+            address,
+            CecCommand::get_cec_version(address),
+            |message| match message {
+                CecMessage::CecVersion { version } => Some(version),
+                _ => None,
+            },
+        )
+        .unwrap_or(CecVersion::VersionUnknown)
+    }
+
+    fn get_device_menu_language(
+        &self,
+        address: CecLogicalAddress,
+    ) -> CecConnectionResult<CecMenuLanguage> {
+        self.request_result(
+            address,
+            CecCommand::get_menu_language(address),
+            |message| match message {
+                CecMessage::SetMenuLanguage { language } => Some(language),
+                _ => None,
+            },
+        )
+    }
+
+    fn get_device_vendor_id(&self, address: CecLogicalAddress) -> CecVendorId {
+        self.request(
+            address,
+            CecCommand::give_device_vendor_id(address),
+            |message| match message {
+                CecMessage::DeviceVendorId { vendor_id } => Some(vendor_id),
+                _ => None,
+            },
+        )
+        .unwrap_or(CecVendorId::Unknown)
+    }
+
+    fn get_device_physical_address(
+        &self,
+        address: CecLogicalAddress,
+    ) -> Option<CecPhysicalAddress> {
+        self.request(
+            address,
+            CecCommand::give_physical_address(address),
+            |message| match message {
+                CecMessage::ReportPhysicalAddress { address, .. } => Some(address),
+                _ => None,
+            },
+        )
+    }
+
+    fn get_device_osd_name(&self, address: CecLogicalAddress) -> CecConnectionResult<String> {
+        self.request_result(
+            address,
+            CecCommand::give_osd_name(address),
+            |message| match message {
+                CecMessage::SetOsdName { name } => Some(name),
+                _ => None,
+            },
+        )
+    }
+
+    fn poll_device(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        let command = CecCommand::poll(address)?;
+        self.send_acked(command)
+    }
+
+    fn rescan_devices(&self) {
+        let own_address = self.0.state().logical_address;
+        for address in (0u8..15).filter_map(|address| CecLogicalAddress::from_repr(address.into()))
+        {
+            if address != own_address {
+                let _ = self.poll_device(address);
+            }
+        }
+    }
+
+    fn get_active_devices(&self) -> Vec<CecLogicalAddress> {
+        let state = self.0.state();
+        (0u8..15)
+            .filter_map(|address| CecLogicalAddress::from_repr(address.into()))
+            .filter(|address| *address == state.logical_address || state.is_taken(*address))
+            .collect()
+    }
+
+    fn set_stream_path_logical(&self, address: CecLogicalAddress) -> CecConnectionResult<()> {
+        let physical_address = self.request_result(
+            address,
+            CecCommand::give_physical_address(address),
+            |message| match message {
+                CecMessage::ReportPhysicalAddress { address, .. } => Some(address),
+                _ => None,
+            },
+        )?;
+        self.set_stream_path_physical(physical_address)
+    }
+
+    fn set_stream_path_physical(&self, address: CecPhysicalAddress) -> CecConnectionResult<()> {
+        self.send_acked(CecCommand::set_stream_path(address))
+    }
+
+    fn current_configuration(&self) -> CecConnectionResult<CecConfiguration> {
+        let logical_addresses = self
+            .get_logical_addresses()
+            .map_err(|_| CecConnectionResultError::ConfigurationFailed)?;
+        let state = self.0.state();
+        let mut configuration = state
+            .configuration
+            .clone()
+            .ok_or(CecConnectionResultError::ConfigurationFailed)?;
+        configuration.physical_address = Some(state.physical_address);
+        configuration.logical_addresses = logical_addresses;
+        Ok(configuration)
+    }
+
+    fn update_configuration(&self, update: &CecConfigurationUpdate) -> CecConnectionResult<()> {
+        let mut state = self.0.state();
+        let configuration = state
+            .configuration
+            .as_mut()
+            .ok_or(CecConnectionResultError::ConfigurationFailed)?;
+        update.apply(configuration);
+        if let Some(physical_address) = update.physical_address {
+            state.physical_address = physical_address;
+        }
+        Ok(())
+    }
+
+    fn can_persist_configuration(&self) -> bool {
+        true
+    }
+
+    /// Like an adapter with ROM, the persisted configuration is also applied
+    fn persist_configuration(&self, update: &CecConfigurationUpdate) -> CecConnectionResult<()> {
+        self.update_configuration(update)
+    }
+
+    fn adapter_info(&self) -> CecConnectionResult<CecAdapterInfo> {
+        Ok(CecAdapterInfo::new(
+            0,
+            0,
+            &self.current_configuration()?,
+            "CecMockBus".into(),
+        ))
+    }
+}
+
+impl Drop for MockBackend {
+    fn drop(&mut self) {
+        self.0.state().connected = false;
+        *self.0.callbacks() = None;
+    }
+}
+
+#[cfg(test)]
+mod mock_tests {
+    use super::*;
+    use crate::{
+        CecAbortReason, CecCallbackPanicPolicy, CecConnectionCfgBuilder, CecDeviceTypeVec,
+        CecKeypress, CecLogLevel, CecLogMessage, CecOpcode, LibcecAbi,
+    };
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::time::Duration;
+
+    /// Replies to power status requests
+    struct PowerStatusDevice(CecLogicalAddress, CecPowerStatus);
+
+    impl CecMockDevice for PowerStatusDevice {
+        fn logical_address(&self) -> CecLogicalAddress {
+            self.0
+        }
+
+        fn receive(&mut self, command: &CecCommand) -> Vec<CecCommand> {
+            match command.message() {
+                Ok(CecMessage::GiveDevicePowerStatus) => {
+                    vec![CecCommand::report_power_status(command.initiator, self.1).unwrap()]
+                }
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    fn cfg() -> CecConnectionCfgBuilder {
+        CecConnectionCfgBuilder::default()
+            .device_name("test".into())
+            .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+    }
+
+    fn command_cfg(sender: Sender<CecCommand>) -> CecConnectionCfg {
+        cfg()
+            .command_received_callback(Box::new(move |command| sender.send(command).unwrap()))
+            .build()
+            .unwrap()
+    }
+
+    fn from_tv(command: Result<CecCommand, impl Sized>) -> CecCommand {
+        let mut command = command.ok().unwrap();
+        command.initiator = CecLogicalAddress::Tv;
+        command
+    }
+
+    #[test]
+    fn test_transmit_recorded() {
+        let bus = CecMockBus::new();
+        bus.add_device(PowerStatusDevice(CecLogicalAddress::Tv, CecPowerStatus::On));
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        let command = CecCommand::give_osd_name(CecLogicalAddress::Tv).unwrap();
+        connection.transmit(command.clone()).unwrap();
+        assert_eq!(
+            bus.take_transmitted(),
+            vec![CecCommand {
+                initiator: CecLogicalAddress::Playbackdevice1,
+                ..command
+            }]
+        );
+        assert_eq!(bus.transmitted(), vec![]);
+    }
+
+    #[test]
+    fn test_transmit_unknown_initiator() {
+        let bus = CecMockBus::new();
+        bus.add_device(PowerStatusDevice(CecLogicalAddress::Tv, CecPowerStatus::On));
+        let _connection = bus.open(cfg().build().unwrap()).unwrap();
+        let backend = MockBackend(bus.clone());
+        assert_eq!(
+            backend.transmit(CecCommand::give_osd_name(CecLogicalAddress::Tv).unwrap()),
+            Err(CecConnectionResultError::TransmitFailed)
+        );
+        assert_eq!(bus.transmitted(), vec![]);
+    }
+
+    #[test]
+    fn test_transmit_not_acknowledged() {
+        let bus = CecMockBus::new();
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        assert!(connection
+            .transmit(CecCommand::give_osd_name(CecLogicalAddress::Tv).unwrap())
+            .is_err());
+        assert!(connection.poll_device(CecLogicalAddress::Tv).is_err());
+        connection
+            .transmit(CecCommand::request_active_source())
+            .unwrap();
+        assert_eq!(bus.transmitted().len(), 3);
+    }
+
+    #[test]
+    fn test_query_answered_by_device() {
+        let bus = CecMockBus::new();
+        bus.add_device(PowerStatusDevice(
+            CecLogicalAddress::Tv,
+            CecPowerStatus::Standby,
+        ));
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        assert_eq!(
+            connection.get_device_power_status(CecLogicalAddress::Tv),
+            CecPowerStatus::Standby
+        );
+        assert_eq!(
+            connection.get_device_power_status(CecLogicalAddress::Audiosystem),
+            CecPowerStatus::Unknown
+        );
+        assert!(connection.poll_device(CecLogicalAddress::Tv).is_ok());
+    }
+
+    #[test]
+    fn test_free_logical_address_claimed() {
+        let bus = CecMockBus::new();
+        bus.add_device(PowerStatusDevice(
+            CecLogicalAddress::Playbackdevice1,
+            CecPowerStatus::On,
+        ));
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        assert_eq!(
+            CecLogicalAddress::from(connection.get_logical_addresses().unwrap().primary),
+            CecLogicalAddress::Playbackdevice2
+        );
+    }
+
+    #[test]
+    fn test_inject_delivered_to_callbacks() {
+        let bus = CecMockBus::new();
+        let (sender, receiver) = channel();
+        let _connection = bus.open(command_cfg(sender)).unwrap();
+        let command = from_tv(CecCommand::give_osd_name(
+            CecLogicalAddress::Playbackdevice1,
+        ));
+        assert!(bus.inject(command.clone()));
+        assert_eq!(receiver.try_recv(), Ok(command));
+        // Sent to another device, not acknowledged
+        assert!(!bus.inject(from_tv(CecCommand::give_osd_name(
+            CecLogicalAddress::Audiosystem
+        ))));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_inject_event() {
+        let bus = CecMockBus::new();
+        let (sender, receiver) = channel();
+        let _connection = bus
+            .open(
+                cfg()
+                    .key_press_callback(Box::new(move |keypress| sender.send(keypress).unwrap()))
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        let keypress = CecKeypress {
+            keycode: CecUserControlCode::Select,
+            duration: Duration::from_millis(100),
+        };
+        bus.inject_event(CecEvent::Keypress(keypress));
+        assert_eq!(receiver.try_recv(), Ok(keypress));
+    }
+
+    /// Count key press callback calls, the callback panics every time
+    fn panicking_key_press(
+        bus: &CecMockBus,
+        policy: CecCallbackPanicPolicy,
+    ) -> (CecConnection, Receiver<()>, Receiver<CecLogMessage>) {
+        let (call_sender, calls) = channel();
+        let (log_sender, logs) = channel();
+        let connection = bus
+            .open(
+                cfg()
+                    .key_press_callback(Box::new(move |_| {
+                        call_sender.send(()).unwrap();
+                        panic!("boom");
+                    }))
+                    .log_message_callback(Box::new(move |log| log_sender.send(log).unwrap()))
+                    .callback_panic_policy(policy)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        (connection, calls, logs)
+    }
+
+    #[test]
+    fn test_callback_panic() {
+        let keypress = CecEvent::Keypress(CecKeypress {
+            keycode: CecUserControlCode::Select,
+            duration: Duration::from_millis(100),
+        });
+        let bus = CecMockBus::new();
+        let (_connection, calls, logs) =
+            panicking_key_press(&bus, CecCallbackPanicPolicy::LogAndContinue);
+        bus.inject_event(keypress.clone());
+        bus.inject_event(keypress.clone());
+        assert_eq!(calls.try_iter().count(), 2);
+        let logs: Vec<_> = logs.try_iter().collect();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].level, CecLogLevel::Error);
+        assert_eq!(logs[0].message, "key press callback panicked: boom");
+
+        let bus = CecMockBus::new();
+        let (_connection, calls, logs) =
+            panicking_key_press(&bus, CecCallbackPanicPolicy::DisableCallback);
+        bus.inject_event(keypress.clone());
+        bus.inject_event(keypress);
+        assert_eq!(calls.try_iter().count(), 1);
+        assert_eq!(logs.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn test_log_callback_panic() {
+        let bus = CecMockBus::new();
+        let (sender, receiver) = channel();
+        let _connection = bus
+            .open(
+                cfg()
+                    .log_message_callback(Box::new(move |log| {
+                        sender.send(log).unwrap();
+                        panic!("boom");
+                    }))
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        let log = CecLogMessage {
+            message: "hello".into(),
+            level: CecLogLevel::Notice,
+            time: Duration::from_millis(1),
+        };
+        bus.inject_event(CecEvent::LogMessage(log.clone()));
+        // The panic is not reported back to the panicking callback
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![log]);
+    }
+
+    #[test]
+    fn test_callback_may_use_bus() {
+        let bus = CecMockBus::new();
+        let (sender, receiver) = channel();
+        let config = {
+            let bus = bus.clone();
+            cfg()
+                .command_received_callback(Box::new(move |command| {
+                    if command.message() == Ok(CecMessage::GiveOsdName) {
+                        // Delivered after this callback returns
+                        bus.inject(from_tv(CecCommand::give_physical_address(
+                            CecLogicalAddress::Playbackdevice1,
+                        )));
+                    }
+                    sender.send(command.opcode).unwrap();
+                }))
+                .build()
+                .unwrap()
+        };
+        let _connection = bus.open(config).unwrap();
+        bus.inject(from_tv(CecCommand::give_osd_name(
+            CecLogicalAddress::Playbackdevice1,
+        )));
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                crate::CecOpcode::GiveOsdName,
+                crate::CecOpcode::GivePhysicalAddress
+            ]
+        );
+    }
+
+    #[test]
+    fn test_active_source_tracked() {
+        let bus = CecMockBus::new();
+        let (sender, receiver) = channel();
+        let connection = bus
+            .open(
+                cfg()
+                    .source_activated_callback(Box::new(move |address, activated| {
+                        sender.send((address, activated)).unwrap()
+                    }))
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        connection
+            .set_active_source(CecDeviceType::PlaybackDevice)
+            .unwrap();
+        assert_eq!(
+            connection.get_active_source(),
+            CecLogicalAddress::Playbackdevice1
+        );
+        assert!(connection.is_active_source(CecLogicalAddress::Playbackdevice1));
+        assert_eq!(
+            receiver.try_recv(),
+            Ok((CecLogicalAddress::Playbackdevice1, true))
+        );
+
+        let mut other =
+            CecCommand::active_source(CecPhysicalAddress::from_ports([2, 0, 0, 0]).unwrap());
+        other.initiator = CecLogicalAddress::Playbackdevice2;
+        bus.inject(other);
+        assert_eq!(
+            connection.get_active_source(),
+            CecLogicalAddress::Playbackdevice2
+        );
+        assert_eq!(
+            receiver.try_recv(),
+            Ok((CecLogicalAddress::Playbackdevice1, false))
+        );
+    }
+
+    #[test]
+    fn test_single_connection() {
+        let bus = CecMockBus::new();
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        assert!(matches!(
+            bus.open(cfg().build().unwrap()),
+            Err(CecConnectionResultError::AdapterOpenFailed { .. })
+        ));
+        drop(connection);
+        assert!(bus.open(cfg().build().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_update_configuration() {
+        let bus = CecMockBus::new();
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        let configuration = connection.current_configuration().unwrap();
+        assert_eq!(configuration.device_name, "test");
+        assert_eq!(
+            configuration.logical_addresses.primary,
+            KnownCecLogicalAddress::new(CecLogicalAddress::Playbackdevice1).unwrap()
+        );
+        let physical_address = CecPhysicalAddress::from_ports([2, 0, 0, 0]).unwrap();
+        connection
+            .update_configuration(&CecConfigurationUpdate {
+                device_name: Some("renamed".into()),
+                physical_address: Some(physical_address),
+                ..CecConfigurationUpdate::default()
+            })
+            .unwrap();
+        let updated = connection.current_configuration().unwrap();
+        assert_eq!(updated.device_name, "renamed");
+        assert_eq!(updated.physical_address, Some(physical_address));
+        assert_eq!(updated.device_types, configuration.device_types);
+        connection
+            .set_active_source(CecDeviceType::PlaybackDevice)
+            .unwrap();
+        assert_eq!(
+            bus.take_transmitted().pop(),
+            Some(CecCommand {
+                initiator: CecLogicalAddress::Playbackdevice1,
+                ..CecCommand::active_source(physical_address)
+            })
+        );
+    }
+
+    #[test]
+    fn test_persist_configuration() {
+        let bus = CecMockBus::new();
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        assert!(connection.can_persist_configuration());
+        connection
+            .persist_configuration(&CecConfigurationUpdate {
+                device_types: Some(CecDeviceTypeVec::new(CecDeviceType::RecordingDevice)),
+                ..CecConfigurationUpdate::default()
+            })
+            .unwrap();
+        assert_eq!(
+            connection.current_configuration().unwrap().device_types,
+            CecDeviceTypeVec::new(CecDeviceType::RecordingDevice)
+        );
+    }
+
+    #[test]
+    fn test_adapter_info() {
+        let bus = CecMockBus::new();
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        let info = connection.adapter_info().unwrap();
+        assert_eq!(info.lib_info, "CecMockBus");
+        assert_eq!(info.abi, LibcecAbi::compiled());
+    }
+
+    #[test]
+    fn test_transmit_and_wait() {
+        let bus = CecMockBus::new();
+        bus.add_device(PowerStatusDevice(CecLogicalAddress::Tv, CecPowerStatus::On));
+        bus.add_device(crate::CecSimDevice::audio_system());
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        let timeout = Duration::from_millis(50);
+        let reply = connection
+            .transmit_and_wait(
+                CecCommand::give_device_power_status(CecLogicalAddress::Tv).unwrap(),
+                CecOpcode::ReportPowerStatus,
+                timeout,
+            )
+            .unwrap();
+        assert_eq!(reply.initiator, CecLogicalAddress::Tv);
+        assert_eq!(
+            reply.message(),
+            Ok(CecMessage::ReportPowerStatus {
+                status: CecPowerStatus::On
+            })
+        );
+        assert_eq!(
+            connection.transmit_and_wait(
+                CecCommand::give_osd_name(CecLogicalAddress::Tv).unwrap(),
+                CecOpcode::SetOsdName,
+                timeout,
+            ),
+            Err(CecConnectionResultError::Timeout {
+                destination: CecLogicalAddress::Tv
+            })
+        );
+        assert_eq!(
+            connection.transmit_and_wait(
+                CecCommand::get_menu_language(CecLogicalAddress::Audiosystem).unwrap(),
+                CecOpcode::SetMenuLanguage,
+                timeout,
+            ),
+            Err(CecConnectionResultError::FeatureAbort {
+                destination: CecLogicalAddress::Audiosystem,
+                opcode: CecOpcode::GetMenuLanguage,
+                reason: CecAbortReason::Refused,
+            })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let bus = CecMockBus::new();
+        bus.add_device(PowerStatusDevice(CecLogicalAddress::Tv, CecPowerStatus::On));
+        bus.add_device(crate::CecSimDevice::audio_system());
+        let connection = bus.open(cfg().build().unwrap()).unwrap();
+        assert_eq!(
+            connection.poll_device(CecLogicalAddress::Recordingdevice1),
+            Err(CecConnectionResultError::NotAcknowledged {
+                destination: CecLogicalAddress::Recordingdevice1
+            })
+        );
+        assert_eq!(
+            connection.get_device_osd_name(CecLogicalAddress::Tv),
+            Err(CecConnectionResultError::Timeout {
+                destination: CecLogicalAddress::Tv
+            })
+        );
+        let abort = connection
+            .get_device_menu_language(CecLogicalAddress::Audiosystem)
+            .unwrap_err();
+        assert_eq!(
+            abort,
+            CecConnectionResultError::FeatureAbort {
+                destination: CecLogicalAddress::Audiosystem,
+                opcode: CecOpcode::GetMenuLanguage,
+                reason: CecAbortReason::Refused,
+            }
+        );
+        assert_eq!(
+            abort.to_string(),
+            "Audiosystem aborted GetMenuLanguage: Refused"
+        );
+        assert!(matches!(
+            connection.send_standby_devices(CecLogicalAddress::Unknown),
+            Err(CecConnectionResultError::InvalidArgument(_))
+        ));
+    }
+}
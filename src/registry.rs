@@ -0,0 +1,390 @@
+use crate::{
+    CecCommand, CecDeviceInfo, CecLogicalAddress, CecMessage, CecPowerStatus, CecTopology,
+    CecVendorId, CecVersion, FnCommand,
+};
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Change in a `CecDeviceRegistry`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CecDeviceRegistryChange {
+    #[doc = "new state of a device"]
+    Device(CecDeviceInfo),
+    #[doc = "new active source, None when not known"]
+    ActiveSource(Option<CecLogicalAddress>),
+}
+
+pub type FnDeviceRegistryChange = dyn FnMut(&CecDeviceRegistryChange) + Send;
+
+/// Devices on the bus, kept up to date from received commands
+///
+/// The registry does not send anything on the bus, it only observes ReportPhysicalAddress,
+/// SetOsdName, DeviceVendorId, CecVersion, SetMenuLanguage, ReportPowerStatus, Standby,
+/// ActiveSource, InactiveSource and RoutingChange commands. Feed it the received commands
+/// with `command_received_callback`, or by calling `observe` e.g. from an own callback:
+///
+/// ```ignore
+/// let registry = CecDeviceRegistry::new();
+/// let cfg = CecConnectionCfgBuilder::default()
+///     .command_received_callback(registry.command_received_callback())
+///     // ...
+/// ```
+///
+/// Clones share the state. Unknown properties have `Unknown` or `None` values.
+#[derive(Clone, Default)]
+pub struct CecDeviceRegistry(Arc<DeviceRegistryShared>);
+
+#[derive(Default)]
+struct DeviceRegistryShared {
+    state: Mutex<DeviceRegistryState>,
+    listeners: Mutex<Vec<Box<FnDeviceRegistryChange>>>,
+}
+
+#[derive(Default)]
+struct DeviceRegistryState {
+    devices: BTreeMap<u8, CecDeviceInfo>,
+    active_source: Option<CecLogicalAddress>,
+}
+
+fn unknown_device(address: CecLogicalAddress) -> CecDeviceInfo {
+    CecDeviceInfo {
+        logical_address: address,
+        physical_address: None,
+        vendor_id: CecVendorId::Unknown,
+        cec_version: CecVersion::VersionUnknown,
+        osd_name: None,
+        menu_language: None,
+        power_status: CecPowerStatus::Unknown,
+    }
+}
+
+impl DeviceRegistryState {
+    fn update_device<F: FnOnce(&mut CecDeviceInfo)>(
+        &mut self,
+        address: CecLogicalAddress,
+        changes: &mut Vec<CecDeviceRegistryChange>,
+        f: F,
+    ) {
+        if matches!(
+            address,
+            CecLogicalAddress::Unknown | CecLogicalAddress::Unregistered
+        ) {
+            return;
+        }
+        let device = self
+            .devices
+            .entry(address.repr() as u8)
+            .or_insert_with(|| unknown_device(address));
+        let before = device.clone();
+        f(device);
+        if *device != before {
+            changes.push(CecDeviceRegistryChange::Device(device.clone()));
+        }
+    }
+
+    fn set_active_source(
+        &mut self,
+        active_source: Option<CecLogicalAddress>,
+        changes: &mut Vec<CecDeviceRegistryChange>,
+    ) {
+        if self.active_source != active_source {
+            self.active_source = active_source;
+            changes.push(CecDeviceRegistryChange::ActiveSource(active_source));
+        }
+    }
+
+    fn observe(&mut self, command: &CecCommand) -> Vec<CecDeviceRegistryChange> {
+        let mut changes = Vec::new();
+        let message = match command.message() {
+            Ok(message) => message,
+            Err(_) => return changes,
+        };
+        let initiator = command.initiator;
+        match message {
+            CecMessage::ReportPhysicalAddress { address, .. } => {
+                self.update_device(initiator, &mut changes, |device| {
+                    device.physical_address = Some(address)
+                })
+            }
+            CecMessage::SetOsdName { name } => {
+                self.update_device(initiator, &mut changes, |device| {
+                    device.osd_name = Some(name)
+                })
+            }
+            CecMessage::DeviceVendorId { vendor_id } => {
+                self.update_device(initiator, &mut changes, |device| {
+                    device.vendor_id = vendor_id
+                })
+            }
+            CecMessage::CecVersion { version } => {
+                self.update_device(initiator, &mut changes, |device| {
+                    device.cec_version = version
+                })
+            }
+            CecMessage::SetMenuLanguage { language } => {
+                self.update_device(initiator, &mut changes, |device| {
+                    device.menu_language = Some(language)
+                })
+            }
+            CecMessage::ReportPowerStatus { status } => {
+                self.update_device(initiator, &mut changes, |device| {
+                    device.power_status = status
+                })
+            }
+            CecMessage::Standby => {
+                let standby: Vec<_> = if command.destination == CecLogicalAddress::Unregistered {
+                    self.devices
+                        .values()
+                        .map(|device| device.logical_address)
+                        .collect()
+                } else {
+                    vec![command.destination]
+                };
+                for address in standby {
+                    self.update_device(address, &mut changes, |device| {
+                        device.power_status = CecPowerStatus::Standby
+                    });
+                    if self.active_source == Some(address) {
+                        self.set_active_source(None, &mut changes);
+                    }
+                }
+            }
+            CecMessage::ActiveSource { address } => {
+                self.update_device(initiator, &mut changes, |device| {
+                    device.physical_address = Some(address);
+                    device.power_status = CecPowerStatus::On;
+                });
+                self.set_active_source(Some(initiator), &mut changes);
+            }
+            CecMessage::InactiveSource { .. } if self.active_source == Some(initiator) => {
+                self.set_active_source(None, &mut changes)
+            }
+            CecMessage::RoutingChange { new_address, .. } => {
+                let active_source = self
+                    .devices
+                    .values()
+                    .find(|device| device.physical_address == Some(new_address))
+                    .map(|device| device.logical_address);
+                self.set_active_source(active_source, &mut changes);
+            }
+            _ => {}
+        }
+        changes
+    }
+}
+
+impl CecDeviceRegistry {
+    pub fn new() -> CecDeviceRegistry {
+        CecDeviceRegistry::default()
+    }
+
+    /// Update the registry from received `command`, and notify the listeners of changes
+    pub fn observe(&self, command: &CecCommand) {
+        let changes = self.state().observe(command);
+        if changes.is_empty() {
+            return;
+        }
+        let mut listeners = self
+            .0
+            .listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for change in changes.iter() {
+            for listener in listeners.iter_mut() {
+                listener(change);
+            }
+        }
+    }
+
+    /// Callback to set as `CecConnectionCfgBuilder::command_received_callback`, feeding
+    /// received commands to this registry
+    pub fn command_received_callback(&self) -> Box<FnCommand> {
+        let registry = self.clone();
+        Box::new(move |command| registry.observe(&command))
+    }
+
+    /// Call `listener` on every change
+    ///
+    /// Listeners are called from the thread observing the commands, i.e. the libcec
+    /// callback thread. They may query the registry, but not add listeners.
+    pub fn on_change(&self, listener: Box<FnDeviceRegistryChange>) {
+        self.0
+            .listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(listener);
+    }
+
+    pub fn device(&self, address: CecLogicalAddress) -> Option<CecDeviceInfo> {
+        self.state().devices.get(&(address.repr() as u8)).cloned()
+    }
+
+    /// Devices seen so far, ordered by logical address
+    pub fn devices(&self) -> Vec<CecDeviceInfo> {
+        self.state().devices.values().cloned().collect()
+    }
+
+    /// Active source, `None` when not known
+    pub fn active_source(&self) -> Option<CecLogicalAddress> {
+        self.state().active_source
+    }
+
+    /// Whether the device is known to be switched on
+    pub fn is_on(&self, address: CecLogicalAddress) -> bool {
+        self.device(address)
+            .map_or(false, |device| device.power_status == CecPowerStatus::On)
+    }
+
+    /// Topology of the devices with known physical address
+    pub fn topology(&self) -> CecTopology {
+        CecTopology::new(self.devices())
+    }
+
+    fn state(&self) -> MutexGuard<'_, DeviceRegistryState> {
+        self.0.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+    use crate::{
+        CecConnectionCfgBuilder, CecDeviceType, CecDeviceTypeVec, CecMenuLanguage, CecMockBus,
+        CecPhysicalAddress, CecSimDevice,
+    };
+    use std::sync::mpsc::channel;
+
+    fn from(initiator: CecLogicalAddress, command: CecCommand) -> CecCommand {
+        CecCommand {
+            initiator,
+            ..command
+        }
+    }
+
+    fn address(ports: [u8; 4]) -> CecPhysicalAddress {
+        CecPhysicalAddress::from_ports(ports).unwrap()
+    }
+
+    #[test]
+    fn test_device_state() {
+        let registry = CecDeviceRegistry::new();
+        let tv = CecLogicalAddress::Tv;
+        registry.observe(&from(
+            tv,
+            CecCommand::report_physical_address(address([0, 0, 0, 0]), CecDeviceType::Tv),
+        ));
+        registry.observe(&from(
+            tv,
+            CecCommand::set_osd_name(CecLogicalAddress::Playbackdevice1, "TV").unwrap(),
+        ));
+        registry.observe(&from(tv, CecCommand::device_vendor_id(CecVendorId::Lg)));
+        registry.observe(&from(
+            tv,
+            CecCommand::set_menu_language(CecMenuLanguage::new("fin").unwrap()),
+        ));
+        registry.observe(&from(
+            tv,
+            CecCommand::report_power_status(CecLogicalAddress::Playbackdevice1, CecPowerStatus::On)
+                .unwrap(),
+        ));
+        assert_eq!(
+            registry.device(tv),
+            Some(CecDeviceInfo {
+                logical_address: tv,
+                physical_address: Some(address([0, 0, 0, 0])),
+                vendor_id: CecVendorId::Lg,
+                cec_version: CecVersion::VersionUnknown,
+                osd_name: Some("TV".into()),
+                menu_language: CecMenuLanguage::new("fin"),
+                power_status: CecPowerStatus::On,
+            })
+        );
+        assert!(registry.is_on(tv));
+
+        registry.observe(&from(
+            CecLogicalAddress::Playbackdevice1,
+            CecCommand::standby(CecLogicalAddress::Unregistered).unwrap(),
+        ));
+        assert!(!registry.is_on(tv));
+        assert_eq!(registry.devices().len(), 1);
+        assert_eq!(registry.device(CecLogicalAddress::Audiosystem), None);
+    }
+
+    #[test]
+    fn test_active_source() {
+        let registry = CecDeviceRegistry::new();
+        let player = CecLogicalAddress::Playbackdevice1;
+        registry.observe(&from(
+            player,
+            CecCommand::active_source(address([1, 0, 0, 0])),
+        ));
+        assert_eq!(registry.active_source(), Some(player));
+        assert!(registry.is_on(player));
+        registry.observe(&from(
+            player,
+            CecCommand::inactive_source(address([1, 0, 0, 0])),
+        ));
+        assert_eq!(registry.active_source(), None);
+
+        registry.observe(&from(
+            CecLogicalAddress::Tv,
+            CecCommand::routing_change(address([2, 0, 0, 0]), address([1, 0, 0, 0])),
+        ));
+        assert_eq!(registry.active_source(), Some(player));
+        registry.observe(&from(
+            CecLogicalAddress::Tv,
+            CecCommand::routing_change(address([1, 0, 0, 0]), address([3, 0, 0, 0])),
+        ));
+        assert_eq!(registry.active_source(), None);
+    }
+
+    #[test]
+    fn test_change_notifications() {
+        let registry = CecDeviceRegistry::new();
+        let (sender, receiver) = channel();
+        registry.on_change(Box::new(move |change| sender.send(change.clone()).unwrap()));
+        let player = CecLogicalAddress::Playbackdevice1;
+        let active_source = from(player, CecCommand::active_source(address([1, 0, 0, 0])));
+        registry.observe(&active_source);
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                CecDeviceRegistryChange::Device(CecDeviceInfo {
+                    physical_address: Some(address([1, 0, 0, 0])),
+                    power_status: CecPowerStatus::On,
+                    ..unknown_device(player)
+                }),
+                CecDeviceRegistryChange::ActiveSource(Some(player)),
+            ]
+        );
+        // Nothing changed
+        registry.observe(&active_source);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_fed_from_connection() {
+        let bus = CecMockBus::new();
+        bus.add_device(CecSimDevice::tv());
+        let registry = CecDeviceRegistry::new();
+        let connection = bus
+            .open(
+                CecConnectionCfgBuilder::default()
+                    .device_name("test".into())
+                    .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+                    .command_received_callback(registry.command_received_callback())
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        connection
+            .get_device_osd_name(CecLogicalAddress::Tv)
+            .unwrap();
+        connection.get_device_physical_address(CecLogicalAddress::Tv);
+        let tv = registry.device(CecLogicalAddress::Tv).unwrap();
+        assert_eq!(tv.osd_name, Some("TV".into()));
+        assert_eq!(tv.physical_address, Some(address([0, 0, 0, 0])));
+        assert_eq!(registry.topology().devices().count(), 1);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{
+    CecCommand, CecConnection, CecConnectionResultError, CecLogicalAddress, CecMessage,
+    CecPhysicalAddress,
+};
+
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Device to switch to with `CecConnection::switch_to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CecSwitchTarget {
+    Logical(CecLogicalAddress),
+    Physical(CecPhysicalAddress),
+}
+
+impl From<CecLogicalAddress> for CecSwitchTarget {
+    fn from(address: CecLogicalAddress) -> Self {
+        CecSwitchTarget::Logical(address)
+    }
+}
+
+impl From<CecPhysicalAddress> for CecSwitchTarget {
+    fn from(address: CecPhysicalAddress) -> Self {
+        CecSwitchTarget::Physical(address)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CecSwitchError {
+    #[doc = "physical address of the logical device is not known"]
+    UnknownPhysicalAddress,
+    #[doc = "stream path could not be sent, with the error of sending it"]
+    TransmitFailed(CecConnectionResultError),
+    #[doc = "no ActiveSource or RoutingInformation confirmation within the timeout"]
+    Timeout,
+}
+
+impl fmt::Display for CecSwitchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CecSwitchError::UnknownPhysicalAddress => {
+                write!(f, "physical address of the device is not known")
+            }
+            CecSwitchError::TransmitFailed(_) => write!(f, "sending the stream path failed"),
+            CecSwitchError::Timeout => write!(f, "switch was not confirmed in time"),
+        }
+    }
+}
+
+impl std::error::Error for CecSwitchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CecSwitchError::TransmitFailed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Received commands forwarded to the threads waiting for a reply
+#[derive(Clone, Default)]
+pub(crate) struct CecCommandWatchers(Arc<Mutex<Vec<Sender<CecCommand>>>>);
+
+impl CecCommandWatchers {
+    /// Receive the commands received from now on, until the receiver is dropped
+    pub(crate) fn watch(&self) -> Receiver<CecCommand> {
+        let (sender, receiver) = channel();
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(sender);
+        receiver
+    }
+
+    pub(crate) fn notify(&self, command: &CecCommand) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|sender| sender.send(command.clone()).is_ok());
+    }
+}
+
+/// Whether `command` confirms that the device at `address` (`physical_address`) is now
+/// the active source
+fn confirms_switch(
+    command: &CecCommand,
+    address: Option<CecLogicalAddress>,
+    physical_address: Option<CecPhysicalAddress>,
+) -> bool {
+    match command.message() {
+        Ok(CecMessage::ActiveSource { address: active }) => {
+            Some(command.initiator) == address || Some(active) == physical_address
+        }
+        Ok(CecMessage::RoutingInformation { address: route }) => Some(route) == physical_address,
+        _ => false,
+    }
+}
+
+impl CecConnection {
+    /// Switch the TV to the device `target`, and wait up to `timeout` for the
+    /// ActiveSource or RoutingInformation confirmation
+    ///
+    /// A logical target is switched to with `set_stream_path_logical`, a physical one
+    /// with `set_stream_path_physical`.
+    ///
+    /// # Errors
+    ///
+    /// - UnknownPhysicalAddress: the logical target did not report its physical address
+    /// - TransmitFailed: the stream path could not be sent, carrying the error of
+    ///   `set_stream_path_logical` or `set_stream_path_physical`
+    /// - Timeout: no confirmation was received within `timeout`
+    pub fn switch_to<T: Into<CecSwitchTarget>>(
+        &self,
+        target: T,
+        timeout: Duration,
+    ) -> Result<(), CecSwitchError> {
+        let deadline = Instant::now() + timeout;
+        let (address, physical_address) = match target.into() {
+            CecSwitchTarget::Logical(address) => {
+                let physical_address = self
+                    .get_device_physical_address(address)
+                    .ok_or(CecSwitchError::UnknownPhysicalAddress)?;
+                (Some(address), physical_address)
+            }
+            CecSwitchTarget::Physical(physical_address) => (None, physical_address),
+        };
+        // Watch before sending, the confirmation may arrive before the send returns
+        let confirmations = self.2.watch();
+        match address {
+            Some(address) => self.set_stream_path_logical(address),
+            None => self.set_stream_path_physical(physical_address),
+        }
+        .map_err(CecSwitchError::TransmitFailed)?;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match confirmations.recv_timeout(remaining) {
+                Ok(command) if confirms_switch(&command, address, Some(physical_address)) => {
+                    return Ok(())
+                }
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => {
+                    return Err(CecSwitchError::Timeout)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod routing_tests {
+    use super::*;
+    use crate::{
+        CecConnectionCfgBuilder, CecDeviceType, CecDeviceTypeVec, CecMockBus, CecSimDevice,
+    };
+
+    fn open(bus: &CecMockBus) -> CecConnection {
+        bus.open(
+            CecConnectionCfgBuilder::default()
+                .device_name("test".into())
+                .device_types(CecDeviceTypeVec::new(CecDeviceType::AudioSystem))
+                .build()
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn address(ports: [u8; 4]) -> CecPhysicalAddress {
+        CecPhysicalAddress::from_ports(ports).unwrap()
+    }
+
+    #[test]
+    fn test_set_stream_path() {
+        let bus = CecMockBus::new();
+        bus.add_device(CecSimDevice::playback_device());
+        let connection = open(&bus);
+        connection
+            .set_stream_path_physical(address([3, 0, 0, 0]))
+            .unwrap();
+        connection
+            .set_stream_path_logical(CecLogicalAddress::Playbackdevice1)
+            .unwrap();
+        let stream_paths: Vec<_> = bus
+            .take_transmitted()
+            .into_iter()
+            .filter(|command| {
+                command.message().ok()
+                    == Some(CecMessage::SetStreamPath {
+                        address: address([3, 0, 0, 0]),
+                    })
+            })
+            .collect();
+        assert_eq!(stream_paths.len(), 2);
+        assert!(connection
+            .set_stream_path_logical(CecLogicalAddress::Playbackdevice2)
+            .is_err());
+    }
+
+    #[test]
+    fn test_switch_to() {
+        let bus = CecMockBus::new();
+        let player = CecSimDevice::playback_device();
+        bus.add_device(player.clone());
+        let connection = open(&bus);
+        connection
+            .switch_to(CecLogicalAddress::Playbackdevice1, Duration::from_secs(1))
+            .unwrap();
+        assert!(player.state().active_source);
+
+        player.update(|state| state.active_source = false);
+        connection
+            .switch_to(address([3, 0, 0, 0]), Duration::from_secs(1))
+            .unwrap();
+        assert!(player.state().active_source);
+    }
+
+    #[test]
+    fn test_switch_to_errors() {
+        let bus = CecMockBus::new();
+        bus.add_device(CecSimDevice::playback_device());
+        let connection = open(&bus);
+        assert_eq!(
+            connection.switch_to(
+                CecLogicalAddress::Playbackdevice2,
+                Duration::from_millis(10)
+            ),
+            Err(CecSwitchError::UnknownPhysicalAddress)
+        );
+        // Nothing at 4.0.0.0 confirms
+        assert_eq!(
+            connection.switch_to(address([4, 0, 0, 0]), Duration::from_millis(10)),
+            Err(CecSwitchError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_switch_error_source() {
+        use std::error::Error;
+
+        let err = CecSwitchError::TransmitFailed(CecConnectionResultError::NotAcknowledged {
+            destination: CecLogicalAddress::Playbackdevice1,
+        });
+        assert_eq!(err.to_string(), "sending the stream path failed");
+        assert_eq!(
+            err.source().map(ToString::to_string),
+            Some(
+                CecConnectionResultError::NotAcknowledged {
+                    destination: CecLogicalAddress::Playbackdevice1
+                }
+                .to_string()
+            )
+        );
+        assert!(CecSwitchError::Timeout.source().is_none());
+    }
+
+    #[test]
+    fn test_confirms_switch() {
+        let player = CecLogicalAddress::Playbackdevice1;
+        let routing_information = CecCommand {
+            initiator: CecLogicalAddress::Audiosystem,
+            ..CecCommand::routing_information(address([1, 1, 0, 0]))
+        };
+        assert!(confirms_switch(
+            &routing_information,
+            None,
+            Some(address([1, 1, 0, 0]))
+        ));
+        assert!(!confirms_switch(
+            &routing_information,
+            Some(player),
+            Some(address([1, 2, 0, 0]))
+        ));
+        let active_source = CecCommand {
+            initiator: player,
+            ..CecCommand::active_source(address([1, 2, 0, 0]))
+        };
+        assert!(confirms_switch(&active_source, Some(player), None));
+        assert!(!confirms_switch(
+            &active_source,
+            None,
+            Some(address([1, 1, 0, 0]))
+        ));
+    }
+}
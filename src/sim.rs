@@ -0,0 +1,482 @@
+use crate::{
+    CecAbortReason, CecCommand, CecDeviceType, CecLogicalAddress, CecMenuLanguage, CecMessage,
+    CecMockDevice, CecPhysicalAddress, CecPowerStatus, CecSystemAudioStatus, CecUserControlCode,
+    CecVendorId, CecVersion, KnownCecAudioStatus,
+};
+
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Maximum volume of a simulated audio system, see `CecSimDeviceState::volume`
+pub const SIM_VOLUME_MAX: u8 = 100;
+
+/// State of a simulated device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CecSimDeviceState {
+    pub logical_address: CecLogicalAddress,
+    pub physical_address: CecPhysicalAddress,
+    pub device_type: CecDeviceType,
+    pub vendor_id: CecVendorId,
+    pub osd_name: String,
+    pub power_status: CecPowerStatus,
+    pub cec_version: CecVersion,
+    #[doc = "menu language reported on request, None to refuse the request"]
+    pub menu_language: Option<CecMenuLanguage>,
+    #[doc = "whether this device is the active source"]
+    pub active_source: bool,
+    #[doc = "audio volume 0..=SIM_VOLUME_MAX, for audio systems"]
+    pub volume: u8,
+    #[doc = "whether audio is muted, for audio systems"]
+    pub muted: bool,
+    #[doc = "whether system audio mode is on, for audio systems"]
+    pub system_audio_mode: bool,
+}
+
+impl CecSimDeviceState {
+    /// TV at 0.0.0.0, switched on
+    pub fn tv() -> CecSimDeviceState {
+        CecSimDeviceState {
+            logical_address: CecLogicalAddress::Tv,
+            physical_address: CecPhysicalAddress::from_ports([0, 0, 0, 0]).unwrap(),
+            device_type: CecDeviceType::Tv,
+            vendor_id: CecVendorId::Samsung,
+            osd_name: "TV".into(),
+            power_status: CecPowerStatus::On,
+            cec_version: CecVersion::Version14,
+            menu_language: CecMenuLanguage::new("eng"),
+            active_source: false,
+            volume: 0,
+            muted: false,
+            system_audio_mode: false,
+        }
+    }
+
+    /// Audio system at 2.0.0.0, switched on, system audio mode off
+    pub fn audio_system() -> CecSimDeviceState {
+        CecSimDeviceState {
+            logical_address: CecLogicalAddress::Audiosystem,
+            physical_address: CecPhysicalAddress::from_ports([2, 0, 0, 0]).unwrap(),
+            device_type: CecDeviceType::AudioSystem,
+            vendor_id: CecVendorId::Yamaha,
+            osd_name: "AVR".into(),
+            menu_language: None,
+            volume: 30,
+            ..CecSimDeviceState::tv()
+        }
+    }
+
+    /// Playback device at 3.0.0.0, switched on
+    pub fn playback_device() -> CecSimDeviceState {
+        CecSimDeviceState {
+            logical_address: CecLogicalAddress::Playbackdevice1,
+            physical_address: CecPhysicalAddress::from_ports([3, 0, 0, 0]).unwrap(),
+            device_type: CecDeviceType::PlaybackDevice,
+            vendor_id: CecVendorId::Sony,
+            osd_name: "Player".into(),
+            menu_language: None,
+            ..CecSimDeviceState::tv()
+        }
+    }
+
+    fn is_audio_system(&self) -> bool {
+        self.device_type == CecDeviceType::AudioSystem
+    }
+
+    fn is_source(&self) -> bool {
+        self.device_type != CecDeviceType::Tv
+    }
+
+    fn audio_status(&self) -> KnownCecAudioStatus {
+        KnownCecAudioStatus::new(self.volume, self.muted)
+    }
+
+    fn system_audio_status(&self) -> CecSystemAudioStatus {
+        if self.system_audio_mode {
+            CecSystemAudioStatus::On
+        } else {
+            CecSystemAudioStatus::Off
+        }
+    }
+
+    fn standby(&mut self) {
+        self.power_status = CecPowerStatus::Standby;
+        self.active_source = false;
+        self.system_audio_mode = false;
+    }
+
+    fn user_control_pressed(&mut self, keycode: CecUserControlCode) {
+        match keycode {
+            CecUserControlCode::Power | CecUserControlCode::PowerToggleFunction => {
+                if self.power_status == CecPowerStatus::On {
+                    self.standby();
+                } else {
+                    self.power_status = CecPowerStatus::On;
+                }
+            }
+            CecUserControlCode::PowerOnFunction => self.power_status = CecPowerStatus::On,
+            CecUserControlCode::PowerOffFunction => self.standby(),
+            CecUserControlCode::VolumeUp if self.is_audio_system() => {
+                self.volume = self.volume.saturating_add(1).min(SIM_VOLUME_MAX);
+                self.muted = false;
+            }
+            CecUserControlCode::VolumeDown if self.is_audio_system() => {
+                self.volume = self.volume.saturating_sub(1);
+                self.muted = false;
+            }
+            CecUserControlCode::Mute if self.is_audio_system() => self.muted = !self.muted,
+            CecUserControlCode::MuteFunction if self.is_audio_system() => self.muted = true,
+            CecUserControlCode::RestoreVolumeFunction if self.is_audio_system() => {
+                self.muted = false
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle `command`, returning the replies
+    fn receive(&mut self, command: &CecCommand) -> Vec<CecCommand> {
+        let to = command.initiator;
+        let directed = command.destination == self.logical_address;
+        let message = match command.message() {
+            Ok(message) => message,
+            Err(_) if directed => {
+                return CecCommand::feature_abort(
+                    to,
+                    command.opcode,
+                    CecAbortReason::InvalidOperand,
+                )
+                .into_iter()
+                .collect()
+            }
+            Err(_) => return Vec::new(),
+        };
+        let reply = match message {
+            CecMessage::Poll | CecMessage::FeatureAbort { .. } | CecMessage::UserControlRelease => {
+                return Vec::new()
+            }
+            CecMessage::GivePhysicalAddress => Ok(CecCommand::report_physical_address(
+                self.physical_address,
+                self.device_type,
+            )),
+            CecMessage::GiveDevicePowerStatus => {
+                CecCommand::report_power_status(to, self.power_status)
+            }
+            CecMessage::GiveOsdName => CecCommand::set_osd_name(to, &self.osd_name),
+            CecMessage::GiveDeviceVendorId => Ok(CecCommand::device_vendor_id(self.vendor_id)),
+            CecMessage::GetCecVersion => CecCommand::cec_version(to, self.cec_version),
+            CecMessage::GetMenuLanguage => match self.menu_language {
+                Some(language) => Ok(CecCommand::set_menu_language(language)),
+                None => CecCommand::feature_abort(to, command.opcode, CecAbortReason::Refused),
+            },
+            CecMessage::Standby => {
+                self.standby();
+                return Vec::new();
+            }
+            CecMessage::ImageViewOn | CecMessage::TextViewOn
+                if self.device_type == CecDeviceType::Tv =>
+            {
+                self.power_status = CecPowerStatus::On;
+                return Vec::new();
+            }
+            CecMessage::UserControlPressed { keycode } => {
+                self.user_control_pressed(keycode);
+                return Vec::new();
+            }
+            CecMessage::ActiveSource { .. } => {
+                self.active_source = false;
+                return Vec::new();
+            }
+            CecMessage::SetStreamPath { address } => {
+                if !self.is_source() || address != self.physical_address {
+                    return Vec::new();
+                }
+                self.power_status = CecPowerStatus::On;
+                self.active_source = true;
+                Ok(CecCommand::active_source(self.physical_address))
+            }
+            CecMessage::RequestActiveSource => {
+                if !self.active_source {
+                    return Vec::new();
+                }
+                Ok(CecCommand::active_source(self.physical_address))
+            }
+            CecMessage::GiveAudioStatus if self.is_audio_system() => {
+                CecCommand::report_audio_status(to, self.audio_status())
+            }
+            CecMessage::SystemAudioModeRequest { address } if self.is_audio_system() => {
+                self.system_audio_mode = address.is_some();
+                if self.system_audio_mode {
+                    self.power_status = CecPowerStatus::On;
+                }
+                CecCommand::set_system_audio_mode(
+                    CecLogicalAddress::Unregistered,
+                    self.system_audio_status(),
+                )
+            }
+            CecMessage::GiveSystemAudioModeStatus if self.is_audio_system() => {
+                CecCommand::system_audio_mode_status(to, self.system_audio_status())
+            }
+            _ if directed => {
+                CecCommand::feature_abort(to, command.opcode, CecAbortReason::UnrecognizedOpcode)
+            }
+            _ => return Vec::new(),
+        };
+        reply.into_iter().collect()
+    }
+}
+
+/// Simulated device, to add on a `CecMockBus`
+///
+/// The device answers polls, physical address, power status, OSD name, vendor id,
+/// CEC version and menu language requests, goes to standby on `Standby`, and follows
+/// power keys. Sources become the active source on `SetStreamPath` to their physical
+/// address. Audio systems additionally handle volume keys, audio status and system
+/// audio mode requests. Directed commands the device does not support are answered
+/// with `FeatureAbort`.
+///
+/// Clones share the state, so keep a clone to inspect or change the device after
+/// adding it to the bus.
+#[derive(Debug, Clone)]
+pub struct CecSimDevice(Arc<Mutex<CecSimDeviceState>>);
+
+impl CecSimDevice {
+    pub fn new(state: CecSimDeviceState) -> CecSimDevice {
+        CecSimDevice(Arc::new(Mutex::new(state)))
+    }
+
+    /// See `CecSimDeviceState::tv`
+    pub fn tv() -> CecSimDevice {
+        CecSimDevice::new(CecSimDeviceState::tv())
+    }
+
+    /// See `CecSimDeviceState::audio_system`
+    pub fn audio_system() -> CecSimDevice {
+        CecSimDevice::new(CecSimDeviceState::audio_system())
+    }
+
+    /// See `CecSimDeviceState::playback_device`
+    pub fn playback_device() -> CecSimDevice {
+        CecSimDevice::new(CecSimDeviceState::playback_device())
+    }
+
+    /// Current state of the device
+    pub fn state(&self) -> CecSimDeviceState {
+        self.lock().clone()
+    }
+
+    /// Change the state of the device
+    pub fn update<F: FnOnce(&mut CecSimDeviceState)>(&self, f: F) {
+        f(&mut self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CecSimDeviceState> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl CecMockDevice for CecSimDevice {
+    fn logical_address(&self) -> CecLogicalAddress {
+        self.lock().logical_address
+    }
+
+    fn receive(&mut self, command: &CecCommand) -> Vec<CecCommand> {
+        self.lock().receive(command)
+    }
+}
+
+#[cfg(test)]
+mod sim_tests {
+    use super::*;
+    use crate::{CecConnection, CecConnectionCfgBuilder, CecDeviceTypeVec, CecMockBus, CecOpcode};
+    use std::sync::mpsc::{channel, Receiver};
+
+    struct Sim {
+        bus: CecMockBus,
+        tv: CecSimDevice,
+        audio_system: CecSimDevice,
+        player: CecSimDevice,
+        connection: CecConnection,
+        received: Receiver<CecCommand>,
+    }
+
+    fn sim() -> Sim {
+        let bus = CecMockBus::new();
+        let tv = CecSimDevice::tv();
+        let audio_system = CecSimDevice::audio_system();
+        let player = CecSimDevice::playback_device();
+        bus.add_device(tv.clone());
+        bus.add_device(audio_system.clone());
+        bus.add_device(player.clone());
+        let (sender, received) = channel();
+        let connection = bus
+            .open(
+                CecConnectionCfgBuilder::default()
+                    .device_name("test".into())
+                    .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+                    .command_received_callback(Box::new(move |command| {
+                        sender.send(command).unwrap()
+                    }))
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        Sim {
+            bus,
+            tv,
+            audio_system,
+            player,
+            connection,
+            received,
+        }
+    }
+
+    #[test]
+    fn test_queries() {
+        let sim = sim();
+        let connection = &sim.connection;
+        assert!(connection.poll_device(CecLogicalAddress::Tv).is_ok());
+        assert!(connection.poll_device(CecLogicalAddress::Tuner1).is_err());
+        assert_eq!(
+            connection.get_device_power_status(CecLogicalAddress::Tv),
+            CecPowerStatus::On
+        );
+        assert_eq!(
+            connection.get_device_physical_address(CecLogicalAddress::Audiosystem),
+            CecPhysicalAddress::from_ports([2, 0, 0, 0])
+        );
+        assert_eq!(
+            connection
+                .get_device_osd_name(CecLogicalAddress::Playbackdevice1)
+                .unwrap(),
+            "Player"
+        );
+        assert_eq!(
+            connection.get_device_vendor_id(CecLogicalAddress::Tv),
+            CecVendorId::Samsung
+        );
+        assert_eq!(
+            connection.get_device_cec_version(CecLogicalAddress::Tv),
+            CecVersion::Version14
+        );
+        assert_eq!(
+            connection
+                .get_device_menu_language(CecLogicalAddress::Tv)
+                .unwrap(),
+            CecMenuLanguage::new("eng").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_power() {
+        let sim = sim();
+        sim.connection
+            .send_standby_devices(CecLogicalAddress::Tv)
+            .unwrap();
+        assert_eq!(sim.tv.state().power_status, CecPowerStatus::Standby);
+        assert_eq!(
+            sim.connection
+                .get_device_power_status(CecLogicalAddress::Tv),
+            CecPowerStatus::Standby
+        );
+        assert_eq!(sim.player.state().power_status, CecPowerStatus::On);
+
+        sim.connection
+            .send_power_on_devices(CecLogicalAddress::Tv)
+            .unwrap();
+        assert_eq!(sim.tv.state().power_status, CecPowerStatus::On);
+
+        sim.connection
+            .send_standby_devices(CecLogicalAddress::Unregistered)
+            .unwrap();
+        assert_eq!(sim.player.state().power_status, CecPowerStatus::Standby);
+        sim.connection
+            .send_power_on_devices(CecLogicalAddress::Playbackdevice1)
+            .unwrap();
+        assert_eq!(sim.player.state().power_status, CecPowerStatus::On);
+    }
+
+    #[test]
+    fn test_volume() {
+        let sim = sim();
+        let status = sim.connection.volume_up(true).unwrap();
+        assert_eq!((status.volume(), status.is_muted()), (31, false));
+        let status = sim.connection.audio_toggle_mute().unwrap();
+        assert_eq!((status.volume(), status.is_muted()), (31, true));
+        let status = sim.connection.volume_down(true).unwrap();
+        assert_eq!((status.volume(), status.is_muted()), (30, false));
+        assert_eq!(sim.audio_system.state().volume, 30);
+
+        sim.audio_system
+            .update(|state| state.volume = SIM_VOLUME_MAX);
+        let status = sim.connection.volume_up(true).unwrap();
+        assert_eq!(status.volume(), SIM_VOLUME_MAX);
+    }
+
+    #[test]
+    fn test_system_audio_mode() {
+        let sim = sim();
+        sim.connection
+            .transmit(
+                CecCommand::system_audio_mode_request(
+                    CecLogicalAddress::Audiosystem,
+                    CecPhysicalAddress::from_ports([3, 0, 0, 0]),
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        assert!(sim.audio_system.state().system_audio_mode);
+        sim.connection
+            .transmit(
+                CecCommand::system_audio_mode_request(CecLogicalAddress::Audiosystem, None)
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(!sim.audio_system.state().system_audio_mode);
+    }
+
+    #[test]
+    fn test_active_source() {
+        let sim = sim();
+        assert_eq!(
+            sim.connection.get_active_source(),
+            CecLogicalAddress::Unknown
+        );
+        sim.connection
+            .transmit(CecCommand::set_stream_path(
+                sim.player.state().physical_address,
+            ))
+            .unwrap();
+        assert!(sim.player.state().active_source);
+        assert_eq!(
+            sim.connection.get_active_source(),
+            CecLogicalAddress::Playbackdevice1
+        );
+
+        sim.connection
+            .set_active_source(CecDeviceType::PlaybackDevice)
+            .unwrap();
+        assert!(!sim.player.state().active_source);
+        assert_eq!(
+            sim.connection.get_active_source(),
+            CecLogicalAddress::Playbackdevice2
+        );
+    }
+
+    #[test]
+    fn test_unsupported_feature_aborted() {
+        let sim = sim();
+        sim.connection
+            .transmit(CecCommand::give_audio_status(CecLogicalAddress::Tv).unwrap())
+            .unwrap();
+        let reply = sim.received.try_recv().unwrap();
+        assert_eq!(reply.initiator, CecLogicalAddress::Tv);
+        assert_eq!(reply.destination, CecLogicalAddress::Playbackdevice2);
+        assert_eq!(
+            reply.message(),
+            Ok(CecMessage::FeatureAbort {
+                opcode: CecOpcode::GiveAudioStatus,
+                reason: CecAbortReason::UnrecognizedOpcode,
+            })
+        );
+        // Replies are not recorded as transmitted
+        assert_eq!(sim.bus.transmitted().len(), 1);
+    }
+}
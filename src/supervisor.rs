@@ -0,0 +1,453 @@
+use crate::{
+    CecConnection, CecConnectionCfg, CecConnectionResult, CecConnectionResultError, LibcecAlert,
+};
+
+use log::{trace, warn};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// State of the connection kept open by `CecSupervisor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CecConnectionState {
+    #[doc = "connection is open"]
+    Connected,
+    #[doc = "libcec reported `LibcecAlert::ConnectionLost`, the connection is closed"]
+    Lost,
+    #[doc = "waiting before reopening, `attempt` counts from 1"]
+    Reconnecting { attempt: u32 },
+    #[doc = "all reconnect attempts failed with the last error, the supervisor gives up"]
+    Failed(CecConnectionResultError),
+}
+
+pub type FnConnectionState = dyn FnMut(CecConnectionState) + Send;
+
+/// Delay between reconnect attempts, doubling from `initial` up to `max`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CecReconnectBackoff {
+    pub initial: Duration,
+    pub max: Duration,
+    #[doc = "attempts before giving up with `CecConnectionState::Failed`, None retries forever"]
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for CecReconnectBackoff {
+    fn default() -> Self {
+        CecReconnectBackoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            max_attempts: None,
+        }
+    }
+}
+
+impl CecReconnectBackoff {
+    /// Delay before reconnect `attempt`, counting from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial
+            .checked_mul(factor)
+            .map_or(self.max, |delay| delay.min(self.max))
+    }
+}
+
+enum SupervisorMessage {
+    #[doc = "alert of the connection opened as `generation`, counting opens from 0"]
+    ConnectionLost {
+        generation: u64,
+    },
+    Stop,
+}
+
+type SharedConnection = Arc<Mutex<Option<CecConnection>>>;
+
+/// Keeps a connection open, reopening it when the adapter is lost
+///
+/// The connection is opened from a fresh `CecConnectionCfg` every time, since opening
+/// consumes the callbacks of the configuration. On `LibcecAlert::ConnectionLost` the
+/// connection is closed, and reopened with `CecReconnectBackoff` between the attempts.
+/// The alert is still passed to the alert callback of the configuration.
+pub struct CecSupervisor {
+    connection: SharedConnection,
+    messages: Sender<SupervisorMessage>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CecSupervisor {
+    /// Open the connection configured by `config` with `CecConnectionCfg::open`, and keep
+    /// it open
+    ///
+    /// `state_callback` is called with `Connected` once open, and on every later state
+    /// change.
+    ///
+    /// # Errors
+    ///
+    /// The first open is not retried, its error is returned as is, see
+    /// `CecConnectionCfg::open`
+    pub fn open<F>(
+        config: F,
+        backoff: CecReconnectBackoff,
+        state_callback: Box<FnConnectionState>,
+    ) -> CecConnectionResult<CecSupervisor>
+    where
+        F: FnMut() -> CecConnectionCfg + Send + 'static,
+    {
+        CecSupervisor::open_with(config, CecConnectionCfg::open, backoff, state_callback)
+    }
+
+    /// Like `open`, but opening the connection with `open`, e.g. `CecMockBus::open`
+    ///
+    /// # Errors
+    ///
+    /// The first open is not retried, its error is returned as is
+    pub fn open_with<F, O>(
+        mut config: F,
+        mut open: O,
+        backoff: CecReconnectBackoff,
+        mut state_callback: Box<FnConnectionState>,
+    ) -> CecConnectionResult<CecSupervisor>
+    where
+        F: FnMut() -> CecConnectionCfg + Send + 'static,
+        O: FnMut(CecConnectionCfg) -> CecConnectionResult<CecConnection> + Send + 'static,
+    {
+        let (sender, messages) = channel();
+        let connection = open(watch_connection_lost(config(), sender.clone(), 0))?;
+        state_callback(CecConnectionState::Connected);
+        let connection: SharedConnection = Arc::new(Mutex::new(Some(connection)));
+        let supervised = connection.clone();
+        let lost_sender = sender.clone();
+        let thread = thread::spawn(move || {
+            let mut reopen = move |generation| {
+                open(watch_connection_lost(
+                    config(),
+                    lost_sender.clone(),
+                    generation,
+                ))
+            };
+            supervise(
+                &supervised,
+                &messages,
+                &mut reopen,
+                backoff,
+                &mut *state_callback,
+            )
+        });
+        Ok(CecSupervisor {
+            connection,
+            messages: sender,
+            thread: Some(thread),
+        })
+    }
+
+    /// Call `f` with the connection. Returns `None` while the connection is lost
+    ///
+    /// Reconnecting waits until `f` returns.
+    pub fn with_connection<R, F: FnOnce(&CecConnection) -> R>(&self, f: F) -> Option<R> {
+        lock(&self.connection).as_ref().map(f)
+    }
+
+    /// Whether the connection is currently open
+    pub fn is_connected(&self) -> bool {
+        lock(&self.connection).is_some()
+    }
+}
+
+impl Drop for CecSupervisor {
+    fn drop(&mut self) {
+        // The thread may already be gone after Failed
+        let _ = self.messages.send(SupervisorMessage::Stop);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                warn!("CecSupervisor: supervisor thread panicked");
+            }
+        }
+    }
+}
+
+fn lock(connection: &SharedConnection) -> MutexGuard<'_, Option<CecConnection>> {
+    connection.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Chain an alert callback reporting `LibcecAlert::ConnectionLost` of the connection
+/// opened as `generation` to `sender`
+fn watch_connection_lost(
+    mut config: CecConnectionCfg,
+    sender: Sender<SupervisorMessage>,
+    generation: u64,
+) -> CecConnectionCfg {
+    let mut alert_callback = config.alert_callback.take();
+    config.alert_callback = Some(Box::new(move |alert, parameter| {
+        if alert == LibcecAlert::ConnectionLost {
+            // The supervisor is gone when the connection is being dropped
+            let _ = sender.send(SupervisorMessage::ConnectionLost { generation });
+        }
+        if let Some(alert_callback) = &mut alert_callback {
+            alert_callback(alert, parameter);
+        }
+    }));
+    config
+}
+
+/// Wait for `delay`. Returns false when stopped
+fn wait(messages: &Receiver<SupervisorMessage>, delay: Duration) -> bool {
+    let deadline = Instant::now() + delay;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match messages.recv_timeout(remaining) {
+            Ok(SupervisorMessage::Stop) | Err(RecvTimeoutError::Disconnected) => return false,
+            // No connection is open, the alert is from a closed one
+            Ok(SupervisorMessage::ConnectionLost { .. }) => {}
+            Err(RecvTimeoutError::Timeout) => return true,
+        }
+    }
+}
+
+/// Reopen the connection whenever it is lost, until stopped or reconnecting fails
+///
+/// `reopen` is called with the generation of the connection to open.
+fn supervise(
+    connection: &SharedConnection,
+    messages: &Receiver<SupervisorMessage>,
+    reopen: &mut dyn FnMut(u64) -> CecConnectionResult<CecConnection>,
+    backoff: CecReconnectBackoff,
+    state_callback: &mut FnConnectionState,
+) {
+    let mut generation = 0;
+    loop {
+        match messages.recv() {
+            Ok(SupervisorMessage::ConnectionLost { generation: lost }) if lost == generation => {}
+            // Alerts of earlier connections are stale
+            Ok(SupervisorMessage::ConnectionLost { .. }) => continue,
+            Ok(SupervisorMessage::Stop) | Err(_) => return,
+        }
+        // Dropping destroys libcec and the callbacks of the lost connection
+        lock(connection).take();
+        state_callback(CecConnectionState::Lost);
+        let mut attempt = 1;
+        let reopened = loop {
+            state_callback(CecConnectionState::Reconnecting { attempt });
+            if !wait(messages, backoff.delay(attempt)) {
+                return;
+            }
+            // Every attempt is a new generation, a failed open may still alert
+            generation += 1;
+            match reopen(generation) {
+                Ok(reopened) => break reopened,
+                Err(err) => {
+                    trace!(
+                        "CecSupervisor: reconnect attempt {} failed: {}",
+                        attempt,
+                        err
+                    );
+                    if backoff.max_attempts.map_or(false, |max| attempt >= max) {
+                        state_callback(CecConnectionState::Failed(err));
+                        return;
+                    }
+                    attempt += 1;
+                }
+            }
+        };
+        *lock(connection) = Some(reopened);
+        state_callback(CecConnectionState::Connected);
+    }
+}
+
+#[cfg(test)]
+mod supervisor_tests {
+    use super::*;
+    use crate::{
+        CecConnectionCfgBuilder, CecDeviceType, CecDeviceTypeVec, CecEvent, CecMockBus,
+        LibcecParameter,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn cfg() -> CecConnectionCfg {
+        CecConnectionCfgBuilder::default()
+            .device_name("test".into())
+            .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+            .build()
+            .unwrap()
+    }
+
+    fn backoff(max_attempts: Option<u32>) -> CecReconnectBackoff {
+        CecReconnectBackoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(10),
+            max_attempts,
+        }
+    }
+
+    /// Open on `bus`, failing the first `failures` reopens. Returns the state changes
+    fn supervise_bus(
+        bus: &CecMockBus,
+        failures: u32,
+        max_attempts: Option<u32>,
+    ) -> (CecSupervisor, Receiver<CecConnectionState>) {
+        let (sender, states) = channel();
+        let opener = bus.clone();
+        let mut opens = 0;
+        let supervisor = CecSupervisor::open_with(
+            cfg,
+            move |config| {
+                opens += 1;
+                // The first open is not a reconnect
+                if opens > 1 && opens <= failures + 1 {
+                    return Err(CecConnectionResultError::NoAdapterFound);
+                }
+                opener.open(config)
+            },
+            backoff(max_attempts),
+            Box::new(move |state| sender.send(state).unwrap()),
+        )
+        .unwrap();
+        (supervisor, states)
+    }
+
+    fn lose_connection(bus: &CecMockBus) {
+        bus.inject_event(CecEvent::Alert(
+            LibcecAlert::ConnectionLost,
+            LibcecParameter::Unknown,
+        ));
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let backoff = CecReconnectBackoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+            max_attempts: None,
+        };
+        assert_eq!(backoff.delay(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay(3), Duration::from_secs(4));
+        assert_eq!(backoff.delay(5), Duration::from_secs(10));
+        assert_eq!(backoff.delay(100), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_reconnect() {
+        let bus = CecMockBus::new();
+        let (supervisor, states) = supervise_bus(&bus, 1, None);
+        assert_eq!(
+            states.recv_timeout(TIMEOUT),
+            Ok(CecConnectionState::Connected)
+        );
+        assert!(supervisor.is_connected());
+
+        lose_connection(&bus);
+        let expected = [
+            CecConnectionState::Lost,
+            CecConnectionState::Reconnecting { attempt: 1 },
+            CecConnectionState::Reconnecting { attempt: 2 },
+            CecConnectionState::Connected,
+        ];
+        for state in expected.iter() {
+            assert_eq!(states.recv_timeout(TIMEOUT).as_ref(), Ok(state));
+        }
+        assert_eq!(supervisor.with_connection(|_| ()), Some(()));
+
+        // The reopened connection is supervised as well
+        lose_connection(&bus);
+        assert_eq!(states.recv_timeout(TIMEOUT), Ok(CecConnectionState::Lost));
+    }
+
+    #[test]
+    fn test_lost_while_reconnecting() {
+        let bus = CecMockBus::new();
+        let (sender, states) = channel();
+        let opener = bus.clone();
+        let mut opens = 0;
+        let _supervisor = CecSupervisor::open_with(
+            cfg,
+            move |config| {
+                opens += 1;
+                let connection = opener.open(config)?;
+                // The reopened adapter drops again before it is handed out
+                if opens == 2 {
+                    lose_connection(&opener);
+                }
+                Ok(connection)
+            },
+            backoff(None),
+            Box::new(move |state| sender.send(state).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(
+            states.recv_timeout(TIMEOUT),
+            Ok(CecConnectionState::Connected)
+        );
+        lose_connection(&bus);
+        let expected = [
+            CecConnectionState::Lost,
+            CecConnectionState::Reconnecting { attempt: 1 },
+            CecConnectionState::Connected,
+            CecConnectionState::Lost,
+            CecConnectionState::Reconnecting { attempt: 1 },
+            CecConnectionState::Connected,
+        ];
+        for state in expected.iter() {
+            assert_eq!(states.recv_timeout(TIMEOUT).as_ref(), Ok(state));
+        }
+    }
+
+    #[test]
+    fn test_stale_alert_ignored() {
+        let (sender, messages) = channel();
+        let (state_sender, states) = channel();
+        let connection: SharedConnection = Arc::new(Mutex::new(None));
+        let supervised = connection.clone();
+        let thread = thread::spawn(move || {
+            supervise(
+                &supervised,
+                &messages,
+                &mut |_| CecMockBus::new().open(cfg()),
+                backoff(None),
+                &mut move |state| state_sender.send(state).unwrap(),
+            )
+        });
+        sender
+            .send(SupervisorMessage::ConnectionLost { generation: 0 })
+            .unwrap();
+        // Duplicate alert of the lost connection
+        sender
+            .send(SupervisorMessage::ConnectionLost { generation: 0 })
+            .unwrap();
+        let expected = [
+            CecConnectionState::Lost,
+            CecConnectionState::Reconnecting { attempt: 1 },
+            CecConnectionState::Connected,
+        ];
+        for state in expected.iter() {
+            assert_eq!(states.recv_timeout(TIMEOUT).as_ref(), Ok(state));
+        }
+        sender.send(SupervisorMessage::Stop).unwrap();
+        thread.join().unwrap();
+        assert!(states.try_recv().is_err());
+        assert!(lock(&connection).is_some());
+    }
+
+    #[test]
+    fn test_reconnect_failed() {
+        let bus = CecMockBus::new();
+        let (supervisor, states) = supervise_bus(&bus, 2, Some(2));
+        assert_eq!(
+            states.recv_timeout(TIMEOUT),
+            Ok(CecConnectionState::Connected)
+        );
+        lose_connection(&bus);
+        let expected = [
+            CecConnectionState::Lost,
+            CecConnectionState::Reconnecting { attempt: 1 },
+            CecConnectionState::Reconnecting { attempt: 2 },
+            CecConnectionState::Failed(CecConnectionResultError::NoAdapterFound),
+        ];
+        for state in expected.iter() {
+            assert_eq!(states.recv_timeout(TIMEOUT).as_ref(), Ok(state));
+        }
+        assert!(!supervisor.is_connected());
+        assert_eq!(supervisor.with_connection(|_| ()), None);
+    }
+}
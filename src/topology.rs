@@ -0,0 +1,305 @@
+use crate::{CecDeviceInfo, CecLogicalAddress, CecPhysicalAddress};
+
+use std::fmt;
+
+/// HDMI topology of the devices on the bus, arranged by physical address
+///
+/// Build it from the devices discovered with `CecConnection::scan_bus`. Devices without
+/// a physical address are left out. Several logical devices may share a physical address,
+/// e.g. a recorder with a tuner.
+///
+/// `Display` renders the topology as an ASCII tree, one line per physical address:
+///
+/// ```text
+/// 0.0.0.0 Tv "TV"
+/// |-- 1.0.0.0 Audiosystem "AVR"
+/// |   `-- 1.1.0.0 Playbackdevice1 "Player"
+/// `-- 2.0.0.0 Playbackdevice2 "Chromecast"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CecTopology {
+    // Sorted by physical address
+    devices: Vec<(CecPhysicalAddress, CecDeviceInfo)>,
+}
+
+impl CecTopology {
+    pub fn new<I: IntoIterator<Item = CecDeviceInfo>>(devices: I) -> CecTopology {
+        let mut devices: Vec<_> = devices
+            .into_iter()
+            .filter_map(|device| Some((device.physical_address?, device)))
+            .collect();
+        devices.sort_by_key(|(physical_address, device)| {
+            (*physical_address, device.logical_address.repr())
+        });
+        CecTopology { devices }
+    }
+
+    /// Devices ordered by physical address
+    pub fn devices(&self) -> impl Iterator<Item = &CecDeviceInfo> {
+        self.devices.iter().map(|(_, device)| device)
+    }
+
+    pub fn device(&self, address: CecLogicalAddress) -> Option<&CecDeviceInfo> {
+        self.devices()
+            .find(|device| device.logical_address == address)
+    }
+
+    /// Devices at physical address `address`
+    pub fn devices_at(&self, address: CecPhysicalAddress) -> impl Iterator<Item = &CecDeviceInfo> {
+        self.devices
+            .iter()
+            .filter(move |(physical_address, _)| *physical_address == address)
+            .map(|(_, device)| device)
+    }
+
+    fn physical_address(&self, address: CecLogicalAddress) -> Option<CecPhysicalAddress> {
+        self.device(address)?.physical_address
+    }
+
+    /// Devices on the path from the TV to the device at `address`, both included
+    ///
+    /// HDMI switches and other devices without CEC are not part of the path.
+    /// Returns `None` when the device is not in the topology.
+    pub fn path_to(&self, address: CecLogicalAddress) -> Option<Vec<&CecDeviceInfo>> {
+        let target = self.physical_address(address)?;
+        Some(
+            self.devices
+                .iter()
+                .filter(|(physical_address, device)| {
+                    physical_address.is_upstream_of(target) || device.logical_address == address
+                })
+                .map(|(_, device)| device)
+                .collect(),
+        )
+    }
+
+    /// HDMI input of the TV that the device at `address` is connected to, directly or
+    /// through other devices
+    ///
+    /// Returns `None` for the TV itself and when the device is not in the topology.
+    pub fn tv_input(&self, address: CecLogicalAddress) -> Option<u8> {
+        self.physical_address(address)?.port_at(0)
+    }
+
+    /// Devices connected to the inputs of the device at `address`, directly or through
+    /// other devices, e.g. the devices behind the AVR
+    pub fn downstream_of(&self, address: CecLogicalAddress) -> Vec<&CecDeviceInfo> {
+        match self.physical_address(address) {
+            Some(upstream) => self
+                .devices
+                .iter()
+                .filter(|(physical_address, _)| physical_address.is_downstream_of(upstream))
+                .map(|(_, device)| device)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Distinct physical addresses, in order
+    fn physical_addresses(&self) -> Vec<CecPhysicalAddress> {
+        let mut addresses: Vec<_> = self.devices.iter().map(|(address, _)| *address).collect();
+        addresses.dedup();
+        addresses
+    }
+
+    /// Nearest upstream physical address with a device
+    fn parent_of(&self, address: CecPhysicalAddress) -> Option<CecPhysicalAddress> {
+        let mut parent = address.parent();
+        while let Some(address) = parent {
+            if self.devices_at(address).next().is_some() {
+                return Some(address);
+            }
+            parent = address.parent();
+        }
+        None
+    }
+
+    fn fmt_node(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        address: CecPhysicalAddress,
+        prefix: &str,
+    ) -> fmt::Result {
+        write!(f, "{}", address)?;
+        for (i, device) in self.devices_at(address).enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, " {:?}", device.logical_address)?;
+            if let Some(name) = &device.osd_name {
+                write!(f, " {:?}", name)?;
+            }
+        }
+        writeln!(f)?;
+        let children: Vec<_> = self
+            .physical_addresses()
+            .into_iter()
+            .filter(|child| self.parent_of(*child) == Some(address))
+            .collect();
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            write!(f, "{}{}", prefix, if last { "`-- " } else { "|-- " })?;
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "|   " });
+            self.fmt_node(f, *child, &prefix)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<CecDeviceInfo>> for CecTopology {
+    fn from(devices: Vec<CecDeviceInfo>) -> Self {
+        CecTopology::new(devices)
+    }
+}
+
+impl fmt::Display for CecTopology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for address in self.physical_addresses() {
+            if self.parent_of(address).is_none() {
+                self.fmt_node(f, address, "")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod topology_tests {
+    use super::*;
+    use crate::{CecPowerStatus, CecVendorId, CecVersion};
+
+    fn device(address: CecLogicalAddress, ports: Option<[u8; 4]>, name: &str) -> CecDeviceInfo {
+        CecDeviceInfo {
+            logical_address: address,
+            physical_address: ports.and_then(CecPhysicalAddress::from_ports),
+            vendor_id: CecVendorId::Unknown,
+            cec_version: CecVersion::Version14,
+            osd_name: Some(name.into()),
+            menu_language: None,
+            power_status: CecPowerStatus::On,
+        }
+    }
+
+    fn topology() -> CecTopology {
+        CecTopology::new(vec![
+            device(
+                CecLogicalAddress::Playbackdevice2,
+                Some([2, 0, 0, 0]),
+                "Chromecast",
+            ),
+            device(
+                CecLogicalAddress::Playbackdevice1,
+                Some([1, 1, 0, 0]),
+                "Player",
+            ),
+            device(CecLogicalAddress::Tv, Some([0, 0, 0, 0]), "TV"),
+            device(CecLogicalAddress::Audiosystem, Some([1, 0, 0, 0]), "AVR"),
+            device(CecLogicalAddress::Tuner1, Some([1, 0, 0, 0]), "Tuner"),
+            device(CecLogicalAddress::Recordingdevice1, None, "Recorder"),
+        ])
+    }
+
+    fn addresses(devices: &[&CecDeviceInfo]) -> Vec<CecLogicalAddress> {
+        devices
+            .iter()
+            .map(|device| device.logical_address)
+            .collect()
+    }
+
+    #[test]
+    fn test_devices_ordered() {
+        let topology = topology();
+        assert_eq!(
+            addresses(&topology.devices().collect::<Vec<_>>()),
+            vec![
+                CecLogicalAddress::Tv,
+                CecLogicalAddress::Tuner1,
+                CecLogicalAddress::Audiosystem,
+                CecLogicalAddress::Playbackdevice1,
+                CecLogicalAddress::Playbackdevice2,
+            ]
+        );
+        assert!(topology
+            .device(CecLogicalAddress::Recordingdevice1)
+            .is_none());
+    }
+
+    #[test]
+    fn test_path_to() {
+        let topology = topology();
+        assert_eq!(
+            addresses(
+                &topology
+                    .path_to(CecLogicalAddress::Playbackdevice1)
+                    .unwrap()
+            ),
+            vec![
+                CecLogicalAddress::Tv,
+                CecLogicalAddress::Tuner1,
+                CecLogicalAddress::Audiosystem,
+                CecLogicalAddress::Playbackdevice1,
+            ]
+        );
+        assert_eq!(
+            addresses(&topology.path_to(CecLogicalAddress::Tv).unwrap()),
+            vec![CecLogicalAddress::Tv]
+        );
+        assert!(topology
+            .path_to(CecLogicalAddress::Recordingdevice1)
+            .is_none());
+    }
+
+    #[test]
+    fn test_tv_input() {
+        let topology = topology();
+        assert_eq!(
+            topology.tv_input(CecLogicalAddress::Playbackdevice1),
+            Some(1)
+        );
+        assert_eq!(
+            topology.tv_input(CecLogicalAddress::Playbackdevice2),
+            Some(2)
+        );
+        assert_eq!(topology.tv_input(CecLogicalAddress::Tv), None);
+        assert_eq!(topology.tv_input(CecLogicalAddress::Tuner2), None);
+    }
+
+    #[test]
+    fn test_downstream_of() {
+        let topology = topology();
+        assert_eq!(
+            addresses(&topology.downstream_of(CecLogicalAddress::Audiosystem)),
+            vec![CecLogicalAddress::Playbackdevice1]
+        );
+        assert_eq!(topology.downstream_of(CecLogicalAddress::Tv).len(), 4);
+        assert!(topology
+            .downstream_of(CecLogicalAddress::Playbackdevice2)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            topology().to_string(),
+            "0.0.0.0 Tv \"TV\"\n\
+             |-- 1.0.0.0 Tuner1 \"Tuner\", Audiosystem \"AVR\"\n\
+             |   `-- 1.1.0.0 Playbackdevice1 \"Player\"\n\
+             `-- 2.0.0.0 Playbackdevice2 \"Chromecast\"\n"
+        );
+    }
+
+    #[test]
+    fn test_display_without_intermediate_devices() {
+        let topology = CecTopology::new(vec![
+            device(CecLogicalAddress::Playbackdevice1, Some([1, 2, 0, 0]), "A"),
+            device(CecLogicalAddress::Playbackdevice2, Some([1, 2, 3, 0]), "B"),
+            device(CecLogicalAddress::Playbackdevice3, Some([3, 0, 0, 0]), "C"),
+        ]);
+        assert_eq!(
+            topology.to_string(),
+            "1.2.0.0 Playbackdevice1 \"A\"\n\
+             `-- 1.2.3.0 Playbackdevice2 \"B\"\n\
+             3.0.0.0 Playbackdevice3 \"C\"\n"
+        );
+    }
+}
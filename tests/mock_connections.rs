@@ -0,0 +1,69 @@
+//! Several connections open at the same time, each with its own callbacks
+
+use cec_rs::{
+    CecCommand, CecConnectionCfgBuilder, CecDeviceType, CecDeviceTypeVec, CecEvent, CecKeypress,
+    CecLogicalAddress, CecMockBus, CecOpcode, CecSimDevice, CecUserControlCode,
+};
+use libcec_sys::cec_user_control_code;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+const CONNECTIONS: u32 = 4;
+
+#[test]
+fn test_concurrent_mock_connections() {
+    let barrier = Arc::new(Barrier::new(CONNECTIONS as usize));
+    let threads: Vec<_> = (0..CONNECTIONS)
+        .map(|index| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let bus = CecMockBus::new();
+                bus.add_device(CecSimDevice::tv());
+                let (keypress_sender, keypresses) = channel();
+                let (command_sender, commands) = channel();
+                let connection = bus
+                    .open(
+                        CecConnectionCfgBuilder::default()
+                            .device_name(format!("connection{}", index))
+                            .device_types(CecDeviceTypeVec::new(CecDeviceType::PlaybackDevice))
+                            .key_press_callback(Box::new(move |keypress| {
+                                keypress_sender.send(keypress.keycode).unwrap()
+                            }))
+                            .command_received_callback(Box::new(move |command| {
+                                command_sender.send(command.opcode).unwrap()
+                            }))
+                            .build()
+                            .unwrap(),
+                    )
+                    .unwrap();
+                // All connections are open before any of them is used
+                barrier.wait();
+
+                let keycode = CecUserControlCode::from((0x80 + index) as cec_user_control_code);
+                bus.inject_event(CecEvent::Keypress(CecKeypress {
+                    keycode,
+                    duration: Duration::from_millis(100),
+                }));
+                bus.inject(CecCommand {
+                    initiator: CecLogicalAddress::Tv,
+                    ..CecCommand::give_osd_name(CecLogicalAddress::Playbackdevice1).unwrap()
+                });
+                connection
+                    .send_power_on_devices(CecLogicalAddress::Tv)
+                    .unwrap();
+                barrier.wait();
+
+                assert_eq!(keypresses.try_iter().collect::<Vec<_>>(), vec![keycode]);
+                assert!(commands
+                    .try_iter()
+                    .any(|opcode| opcode == CecOpcode::GiveOsdName));
+                assert!(!bus.transmitted().is_empty());
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}